@@ -0,0 +1,175 @@
+//! Throughput of the engine's hot paths: move generation, applying moves,
+//! game-over checks, evaluation, random playouts and search.
+//!
+//! Move generation and the game-over check are also timed against a scan of
+//! the square array, as the engine worked before it kept bitboards, and the
+//! speedup is printed next to them.
+//!
+//! ```text
+//! cargo bench --bench engine
+//! ```
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use latrones::{evaluate_position, EvalWeights, Game, GamePhase, Move, PieceKind, Player, Position, SearchLimit, Searcher, Square};
+
+/// Positions from seeded random games, covering both phases.
+fn sample_positions(count: usize) -> Vec<Position> {
+    let mut seed = 0x5EED_u64;
+    let mut positions = Vec::with_capacity(count);
+    while positions.len() < count {
+        let mut game = Game::new();
+        while !game.game_over() && game.history().len() < 120 && positions.len() < count {
+            positions.push(game.position().clone());
+            let moves = game.legal_moves();
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            game.apply(moves[(seed >> 33) as usize % moves.len()].clone())
+                .expect("sampled moves are legal");
+        }
+    }
+    positions
+}
+
+/// Moves under the default rules found by scanning every square for the
+/// side to move's pieces and looking at each neighbour: steps to empty
+/// squares and single jumps over enemy soldiers, in the engine's order.
+fn array_scan_moves(position: &Position) -> Vec<Move> {
+    let board = position.board();
+    let player = position.current_player();
+    if position.phase() == GamePhase::Placement {
+        return (0..board.len()).filter(|&i| board.get(i) == Square::Empty).map(Move::Place).collect();
+    }
+    let mut moves = Vec::new();
+    for from in 0..board.len() {
+        if board.get(from).owner() != Some(player) {
+            continue;
+        }
+        for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let Some(to) = board.offset(from, dr, dc) else {
+                continue;
+            };
+            match board.get(to) {
+                Square::Empty => moves.push(Move::Step(from, to)),
+                Square::Piece(owner, PieceKind::Soldier) if owner != player => {
+                    if let Some(beyond) = board.offset(to, dr, dc).filter(|&i| board.get(i) == Square::Empty) {
+                        moves.push(Move::Jump(from, to, beyond));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    moves
+}
+
+/// Whether [`array_scan_moves`] would find a move, stopping at the first.
+fn array_scan_has_moves(position: &Position) -> bool {
+    let board = position.board();
+    let player = position.current_player();
+    (0..board.len()).any(|from| match position.phase() {
+        GamePhase::Placement => board.get(from) == Square::Empty,
+        GamePhase::Movement => {
+            board.get(from).owner() == Some(player)
+                && [(-1, 0), (1, 0), (0, -1), (0, 1)].into_iter().any(|(dr, dc)| {
+                    board.offset(from, dr, dc).is_some_and(|to| match board.get(to) {
+                        Square::Empty => true,
+                        Square::Piece(owner, PieceKind::Soldier) if owner != player => {
+                            board.offset(to, dr, dc).is_some_and(|beyond| board.get(beyond) == Square::Empty)
+                        }
+                        _ => false,
+                    })
+                })
+        }
+    })
+}
+
+/// Runs `f` over every position `rounds` times and reports operations per
+/// second, counting whatever `f` returns as operations.
+fn bench(name: &str, positions: &[Position], rounds: usize, mut f: impl FnMut(&Position) -> usize) -> f64 {
+    let start = Instant::now();
+    let mut operations = 0;
+    for _ in 0..rounds {
+        for position in positions {
+            operations += f(black_box(position));
+        }
+    }
+    let rate = operations as f64 / start.elapsed().as_secs_f64();
+    println!("{:<24} {:>12.0} per second", name, rate);
+    rate
+}
+
+/// Reports how many times faster `rate` is than the array scan's.
+fn speedup(rate: f64, array_scan: f64) {
+    println!("{:<24} {:>12.1}x", "  speedup", rate / array_scan);
+}
+
+fn main() {
+    let positions = sample_positions(2000);
+    let weights = EvalWeights::default();
+
+    for position in &positions {
+        assert_eq!(array_scan_moves(position), position.legal_moves(), "{position}");
+        assert_eq!(array_scan_has_moves(position), position.has_legal_moves(), "{position}");
+    }
+
+    let rate = bench("legal_moves", &positions, 50, |position| {
+        black_box(position.legal_moves());
+        1
+    });
+    let array_scan = bench("  array scan", &positions, 50, |position| {
+        black_box(array_scan_moves(position));
+        1
+    });
+    speedup(rate, array_scan);
+    bench("apply (positions)", &positions, 10, |position| {
+        let moves = position.legal_moves();
+        for mv in &moves {
+            let mut child = position.clone();
+            black_box(child.apply(mv.clone()).ok());
+        }
+        moves.len()
+    });
+    let rate = bench("has_legal_moves", &positions, 200, |position| {
+        black_box(position.has_legal_moves());
+        1
+    });
+    let array_scan = bench("  array scan", &positions, 200, |position| {
+        black_box(array_scan_has_moves(position));
+        1
+    });
+    speedup(rate, array_scan);
+    bench("mobility", &positions, 200, |position| {
+        black_box(position.mobility(Player::Light) + position.mobility(Player::Dark));
+        1
+    });
+    bench("evaluate_position", &positions, 50, |position| {
+        black_box(evaluate_position(position, position.current_player(), &weights));
+        1
+    });
+
+    // Random playouts from the opening, counting positions reached
+    let start = Instant::now();
+    let mut seed = 1_u64;
+    let mut reached = 0;
+    for _ in 0..200 {
+        let mut position = Position::new();
+        for _ in 0..200 {
+            let moves = position.legal_moves();
+            if moves.is_empty() || !position.has_legal_moves() {
+                break;
+            }
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            position.apply(moves[(seed >> 33) as usize % moves.len()].clone())
+                .expect("playout moves are legal");
+            reached += 1;
+        }
+    }
+    let seconds = start.elapsed().as_secs_f64();
+    println!("{:<24} {:>12.0} per second", "random playouts", reached as f64 / seconds);
+
+    let start = Instant::now();
+    let result = Searcher::new().search(&Position::starting(), SearchLimit::Depth(7));
+    let seconds = start.elapsed().as_secs_f64();
+    println!("{:<24} {:>12.0} per second", "alpha-beta nodes", result.nodes as f64 / seconds);
+}
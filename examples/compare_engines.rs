@@ -0,0 +1,55 @@
+//! Plays the alpha-beta and MCTS engines against each other from the same
+//! positions, each engine taking both sides.
+//!
+//! ```text
+//! cargo run --release --example compare_engines [depth] [playouts] [games]
+//! ```
+
+use latrones::{Game, MctsConfig, MctsSearcher, Player, Position, SearchLimit, Searcher};
+
+// Games still running after this many plies are scored as draws
+const MAX_PLIES: usize = 300;
+
+fn main() {
+    let mut args = std::env::args().skip(1).map(|arg| arg.parse::<u32>().expect("arguments must be numbers"));
+    let depth = args.next().unwrap_or(3) as u8;
+    let playouts = args.next().unwrap_or(1000);
+    let games = args.next().unwrap_or(4);
+
+    let openings = [("placement", Position::new()), ("starting setup", Position::starting())];
+    for (name, opening) in openings {
+        let mut score = [0.0, 0.0];
+        for game_number in 0..games {
+            // Alternate colours; reseed MCTS so each game differs
+            let alpha_beta_side = if game_number % 2 == 0 { Player::Light } else { Player::Dark };
+            let mut mcts = MctsSearcher::new(MctsConfig {
+                playouts,
+                seed: u64::from(game_number),
+                ..MctsConfig::default()
+            });
+
+            let mut game = Game::from_position(opening.clone());
+            while !game.game_over() && game.history().len() < MAX_PLIES {
+                let mv = if game.current_player() == alpha_beta_side {
+                    Searcher::new().search(game.position(), SearchLimit::Depth(depth)).best_move
+                } else {
+                    mcts.search(game.position()).best_move
+                };
+                game.apply(mv.expect("the game is not over")).expect("engines play legal moves");
+            }
+
+            match game.winner() {
+                Some(winner) if winner == alpha_beta_side => score[0] += 1.0,
+                Some(_) => score[1] += 1.0,
+                None => {
+                    score[0] += 0.5;
+                    score[1] += 0.5;
+                }
+            }
+        }
+        println!(
+            "{}: alpha-beta (depth {}) {} - {} MCTS ({} playouts)",
+            name, depth, score[0], score[1], playouts
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! Counts move sequences from a position, broken down by first move.
+//!
+//! ```text
+//! cargo run --release --example perft [depth] [notation]
+//! ```
+//!
+//! Without a notation the count starts from the standard starting setup.
+
+use latrones::{Move, Position};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let depth = args.next().map_or(3, |arg| {
+        arg.parse::<u8>().ok().filter(|&depth| depth > 0).expect("depth must be a number from 1")
+    });
+    let position = match args.next() {
+        Some(notation) => Position::from_notation(&notation).expect("invalid notation"),
+        None => Position::starting(),
+    };
+
+    let board = position.board();
+    let mut total = 0;
+    for (mv, count) in position.divide(depth) {
+        let separator = if mv.is_jump() { "x" } else { "-" };
+        let text = match mv {
+            Move::Place(square) => board.square_name(square),
+            _ => mv.path().iter().map(|&square| board.square_name(square)).collect::<Vec<_>>().join(separator),
+        };
+        println!("{text:<12} {count}");
+        total += count;
+    }
+    println!("\ntotal        {total}");
+}
@@ -1,6 +1,17 @@
 import init, { GameState, Player } from './pkg/latrones.js';
 
 let game = null;
+// The suggested move and the position it was suggested for
+let hint = null;
+
+// Time controls offered in the menu, in the form `set_time_control` takes
+const TIME_CONTROLS = {
+    'untimed': null,
+    'sudden-death': { kind: 'sudden_death', base_ms: 300000 },
+    'fischer': { kind: 'fischer', base_ms: 180000, increment_ms: 2000 },
+    'bronstein': { kind: 'bronstein', base_ms: 300000, delay_ms: 3000 },
+    'byo-yomi': { kind: 'byo_yomi', base_ms: 60000, period_ms: 10000, periods: 3 },
+};
 
 async function run() {
     await init();
@@ -8,6 +19,7 @@ async function run() {
     renderBoard();
     updateUI();
     setupEventListeners();
+    setInterval(tickClock, 100);
 }
 
 function setupEventListeners() {
@@ -20,6 +32,90 @@ function setupEventListeners() {
         updateUI();
     });
     
+    document.getElementById('undo-btn').addEventListener('click', () => {
+        if (!game || !game.undo()) {
+            return;
+        }
+        renderBoard();
+        updateUI();
+    });
+    
+    document.getElementById('redo-btn').addEventListener('click', () => {
+        if (!game || !game.redo()) {
+            return;
+        }
+        renderBoard();
+        updateUI();
+    });
+    
+    document.getElementById('hint-btn').addEventListener('click', () => {
+        if (!game || game.game_over) {
+            return;
+        }
+        const suggestion = game.suggest_move(500);
+        if (suggestion) {
+            hint = { ...suggestion, hash: game.position_hash() };
+            renderBoard();
+            updateUI();
+        }
+    });
+    
+    document.getElementById('resign-btn').addEventListener('click', () => {
+        if (!game || game.game_over) {
+            return;
+        }
+        game.resign(game.current_player);
+        renderBoard();
+        updateUI();
+    });
+    
+    document.getElementById('draw-btn').addEventListener('click', () => {
+        if (!game || game.game_over) {
+            return;
+        }
+        const player = game.current_player;
+        const opponent = player === Player.Light ? Player.Dark : Player.Light;
+        game.offer_draw(player);
+        // The computer takes a draw unless it thinks it is ahead
+        const accepted = document.getElementById('ai-toggle').checked && opponent === Player.Dark
+            ? game.evaluate(Player.Dark) <= 0
+            : confirm(`${player === Player.Light ? 'Light' : 'Dark'} offers a draw. Accept?`);
+        if (accepted) {
+            game.accept_draw();
+        } else {
+            game.decline_draw();
+        }
+        renderBoard();
+        updateUI();
+    });
+    
+    document.getElementById('danger-toggle').addEventListener('change', () => {
+        if (game) {
+            renderBoard();
+        }
+    });
+    
+    document.getElementById('ai-toggle').addEventListener('change', () => {
+        scheduleAiMove();
+    });
+    
+    document.getElementById('ai-level').addEventListener('change', (event) => {
+        if (game) {
+            game.set_ai_level(event.target.value);
+        }
+    });
+    
+    document.getElementById('time-control').addEventListener('change', (event) => {
+        if (!game) {
+            return;
+        }
+        // A new time control starts a new game
+        game.set_time_control(TIME_CONTROLS[event.target.value]);
+        game.reset();
+        renderBoard();
+        updateUI();
+    });
+    
     document.getElementById('reset-btn').addEventListener('click', () => {
         if (!game) {
             return;
@@ -38,22 +134,30 @@ function renderBoard() {
     const validMoves = game.get_valid_moves();
     const phase = game.phase;
     const selectedSquare = game.selected_square;
+    const selectedPath = Array.from(game.selected_path);
+    const width = game.width;
+    const height = game.height;
+    const hintSquares = currentHint() ? moveSquares(hint.mv) : [];
+    const dangerSquares = document.getElementById('danger-toggle').checked
+        ? game.threats().map((threat) => threat.square)
+        : [];
     
     // Create board with all labels inside
     const board = document.createElement('div');
     board.id = 'game-board';
     board.className = 'board';
     
-    // Create top file labels (A-H) inside board
+    // Create top file labels (A, B, ...) inside board
     const topLabels = document.createElement('div');
     topLabels.className = 'board-labels top-labels';
+    topLabels.style.gridTemplateColumns = `30px repeat(${width}, 1fr) 30px`;
     const emptyTopLeft = document.createElement('div');
     emptyTopLeft.className = 'label-corner';
     topLabels.appendChild(emptyTopLeft);
-    for (let col = 0; col < 8; col++) {
+    for (let col = 0; col < width; col++) {
         const label = document.createElement('div');
         label.className = 'board-label file-label';
-        label.textContent = String.fromCharCode(65 + col); // A-H
+        label.textContent = String.fromCharCode(65 + col); // A, B, ...
         topLabels.appendChild(label);
     }
     const emptyTopRight = document.createElement('div');
@@ -62,21 +166,22 @@ function renderBoard() {
     board.appendChild(topLabels);
     
     // Create board rows with rank labels
-    for (let row = 7; row >= 0; row--) { // Start from row 7 (top) to row 0 (bottom)
+    for (let row = height - 1; row >= 0; row--) { // Start from the top row down to row 0 (bottom)
         const boardRow = document.createElement('div');
         boardRow.className = 'board-row';
         
         // Add rank label on the left
         const rankLabelLeft = document.createElement('div');
         rankLabelLeft.className = 'board-label rank-label rank-label-left';
-        rankLabelLeft.textContent = (row + 1).toString(); // 1-8
+        rankLabelLeft.textContent = (row + 1).toString(); // 1, 2, ...
         boardRow.appendChild(rankLabelLeft);
         
         // Create board squares
         const squaresContainer = document.createElement('div');
         squaresContainer.className = 'board-squares';
-        for (let col = 0; col < 8; col++) {
-            const squareIndex = row * 8 + col;
+        squaresContainer.style.gridTemplateColumns = `repeat(${width}, 1fr)`;
+        for (let col = 0; col < width; col++) {
+            const squareIndex = row * width + col;
             const square = document.createElement('div');
             square.className = 'square';
             square.dataset.index = squareIndex;
@@ -94,13 +199,21 @@ function renderBoard() {
                     content = '●';
                     square.className += ' dark-piece';
                     break;
+                case 3: // LightDux
+                    content = '◎';
+                    square.className += ' light-piece dux';
+                    break;
+                case 4: // DarkDux
+                    content = '◉';
+                    square.className += ' dark-piece dux';
+                    break;
                 default: // Empty
                     square.className += ' empty';
                     content = '';
             }
             
-            // Highlight selected piece
-            if (selectedSquare !== null && selectedSquare !== undefined && selectedSquare === squareIndex) {
+            // Highlight selected piece and the squares picked along its jump
+            if (selectedPath.includes(squareIndex)) {
                 square.className += ' selected';
             }
             
@@ -109,9 +222,19 @@ function renderBoard() {
                 square.className += ' valid-move';
             }
             
+            // Highlight pieces the opponent could capture next
+            if (dangerSquares.includes(squareIndex)) {
+                square.className += ' in-danger';
+            }
+            
+            // Highlight the suggested move
+            if (hintSquares.includes(squareIndex)) {
+                square.className += ' hint';
+            }
+            
             // Highlight selectable piece (only when no piece is selected)
             if (phase === 'movement' && (selectedSquare === null || selectedSquare === undefined) && 
-                validMoves.includes(squareIndex) && (squareType === 1 || squareType === 2)) {
+                validMoves.includes(squareIndex) && squareType !== 0) {
                 square.className += ' selectable';
             }
             
@@ -125,22 +248,23 @@ function renderBoard() {
         // Add rank label on the right
         const rankLabelRight = document.createElement('div');
         rankLabelRight.className = 'board-label rank-label rank-label-right';
-        rankLabelRight.textContent = (row + 1).toString(); // 1-8
+        rankLabelRight.textContent = (row + 1).toString(); // 1, 2, ...
         boardRow.appendChild(rankLabelRight);
         
         board.appendChild(boardRow);
     }
     
-    // Create bottom file labels (A-H) inside board
+    // Create bottom file labels (A, B, ...) inside board
     const bottomLabels = document.createElement('div');
     bottomLabels.className = 'board-labels bottom-labels';
+    bottomLabels.style.gridTemplateColumns = `30px repeat(${width}, 1fr) 30px`;
     const emptyBottomLeft = document.createElement('div');
     emptyBottomLeft.className = 'label-corner';
     bottomLabels.appendChild(emptyBottomLeft);
-    for (let col = 0; col < 8; col++) {
+    for (let col = 0; col < width; col++) {
         const label = document.createElement('div');
         label.className = 'board-label file-label';
-        label.textContent = String.fromCharCode(65 + col); // A-H
+        label.textContent = String.fromCharCode(65 + col); // A, B, ...
         bottomLabels.appendChild(label);
     }
     const emptyBottomRight = document.createElement('div');
@@ -160,14 +284,22 @@ function updateUI() {
     
     const phaseNameEl = document.getElementById('phase-name');
     
+    // Takebacks are refused in timed games
+    document.getElementById('undo-btn').disabled = !game.can_undo;
+    document.getElementById('redo-btn').disabled = !game.can_redo;
+    document.getElementById('hint-btn').disabled = game.game_over;
+    document.getElementById('draw-btn').disabled = game.game_over;
+    document.getElementById('resign-btn').disabled = game.game_over;
+    
+    showClickMessage('');
+    
+    const hintTextEl = document.getElementById('hint-text');
+    hintTextEl.textContent = currentHint() ? `Hint: ${hint.message}` : '';
+    
     if (game.game_over) {
-        const winner = game.winner;
-        if (winner) {
-            const winnerName = winner === Player.Light ? 'Light' : 'Dark';
-            phaseNameEl.textContent = `${winnerName} win`;
-        } else {
-            phaseNameEl.textContent = 'Game Over';
-        }
+        // e.g. "Light wins by elimination" or "draw by repetition"
+        const text = game.result_text;
+        phaseNameEl.textContent = text.charAt(0).toUpperCase() + text.slice(1);
     } else {
         const phase = game.phase;
         phaseNameEl.textContent = phase === 'placement' ? 'Placement' : 'Movement';
@@ -179,11 +311,125 @@ function handleSquareClick(squareIndex) {
         return;
     }
     
-    const success = game.select_square(squareIndex);
-    if (success) {
+    const result = game.select_square(squareIndex, Date.now());
+    if (!result.ok) {
+        if (result.code === 'game_over') {
+            // The flag fell before the click
+            renderBoard();
+            updateUI();
+            game.drain_events();
+        }
+        showClickMessage(result.message);
+        return;
+    }
+    renderBoard();
+    updateUI();
+    if (result.action === 'placed' || result.action === 'moved') {
+        animateCaptures(capturedSquares(game.drain_events()));
+        scheduleAiMove();
+    }
+}
+
+// Explains a rejected click; an empty message clears the explanation
+function showClickMessage(message) {
+    const messageEl = document.getElementById('click-message');
+    messageEl.textContent = message ? message.charAt(0).toUpperCase() + message.slice(1) : '';
+}
+
+function scheduleAiMove() {
+    if (!document.getElementById('ai-toggle').checked) {
+        return;
+    }
+    // Let the browser paint the human move before the search blocks it
+    setTimeout(() => {
+        if (!game || game.game_over || game.current_player !== Player.Dark) {
+            return;
+        }
+        const move = game.ai_move();
+        if (move && game.play_move(move, Date.now())) {
+            renderBoard();
+            updateUI();
+            animateCaptures(capturedSquares(game.drain_events()));
+            // Dark also moves first after placing the last piece
+            scheduleAiMove();
+        }
+    }, 50);
+}
+
+// The clock as of now, or null in untimed games
+function clockState() {
+    return game.clock_state(Date.now());
+}
+
+// Ends the game when a flag falls and redraws both clocks
+function tickClock() {
+    if (!game) {
+        return;
+    }
+    if (game.check_time(Date.now())) {
+        game.drain_events();
         renderBoard();
         updateUI();
     }
+    const state = clockState();
+    for (const [player, id] of [['Light', 'light-clock'], ['Dark', 'dark-clock']]) {
+        const clockEl = document.getElementById(id);
+        clockEl.textContent = state ? formatTime(state[player.toLowerCase()]) : '';
+        clockEl.classList.toggle('running', state !== null && state.running === player);
+    }
+}
+
+// e.g. "4:59", or "0:08 (2)" in byo-yomi: the time left in the current
+// period, with two periods left
+function formatTime(time) {
+    const control = TIME_CONTROLS[document.getElementById('time-control').value];
+    const byoYomi = time.main_ms === 0 && time.periods > 0;
+    const ms = byoYomi ? time.remaining_ms - (time.periods - 1) * control.period_ms : time.main_ms;
+    const seconds = Math.ceil(ms / 1000);
+    const text = `${Math.floor(seconds / 60)}:${String(seconds % 60).padStart(2, '0')}`;
+    return byoYomi ? `${text} (${time.periods})` : text;
+}
+
+// The hint, if it was given for the position on the board
+function currentHint() {
+    return hint && hint.hash === game.position_hash() ? hint : null;
+}
+
+// Squares a move starts on, passes through and ends on
+function moveSquares(move) {
+    if ('Place' in move) {
+        return [move.Place];
+    }
+    if ('Step' in move) {
+        return move.Step;
+    }
+    if ('Jump' in move) {
+        return [move.Jump[0], move.Jump[2]];
+    }
+    return move.MultiJump;
+}
+
+// Squares of the pieces removed by jumps and by custodianship
+function capturedSquares(events) {
+    const squares = [];
+    for (const event of events) {
+        if (event.type === 'piece_jumped' && event.captured) {
+            squares.push(event.over);
+        } else if (event.type === 'pieces_captured') {
+            squares.push(...event.captures.map((capture) => capture.square));
+        }
+    }
+    return squares;
+}
+
+function animateCaptures(squares) {
+    for (const squareIndex of squares) {
+        const square = document.querySelector(`.square[data-index="${squareIndex}"]`);
+        if (square) {
+            square.classList.add('captured');
+            square.addEventListener('animationend', () => square.classList.remove('captured'), { once: true });
+        }
+    }
 }
 
 run().catch(console.error);
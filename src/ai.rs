@@ -0,0 +1,158 @@
+//! Difficulty levels for the computer opponent.
+//!
+//! Weaker levels search less deeply, choose at random among moves that
+//! score nearly as well as the best, and now and then play a random move
+//! outright, so that beginners can win.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::eval::EvalWeights;
+use crate::moves::Move;
+use crate::position::Position;
+use crate::rng::Rng;
+use crate::search::{SearchLimit, Searcher};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AiLevel {
+    Novice,
+    Beginner,
+    #[default]
+    Intermediate,
+    Advanced,
+    /// Full strength: a one-second search with no deliberate mistakes.
+    Expert,
+}
+
+/// Time every level may think for a move, so that a search never blocks the
+/// browser for long. Levels with a fixed depth stop short of it when time
+/// runs out.
+const MOVE_TIME_MS: u32 = 1000;
+
+impl AiLevel {
+    pub const ALL: [AiLevel; 5] = [
+        AiLevel::Novice,
+        AiLevel::Beginner,
+        AiLevel::Intermediate,
+        AiLevel::Advanced,
+        AiLevel::Expert,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AiLevel::Novice => "novice",
+            AiLevel::Beginner => "beginner",
+            AiLevel::Intermediate => "intermediate",
+            AiLevel::Advanced => "advanced",
+            AiLevel::Expert => "expert",
+        }
+    }
+
+    /// Plies searched; the expert searches by time instead.
+    fn depth(self) -> u8 {
+        match self {
+            AiLevel::Novice => 1,
+            AiLevel::Beginner => 2,
+            AiLevel::Intermediate => 3,
+            AiLevel::Advanced => 4,
+            AiLevel::Expert => 4,
+        }
+    }
+
+    /// Percentage of moves played at random.
+    fn blunder_percent(self) -> usize {
+        match self {
+            AiLevel::Novice => 35,
+            AiLevel::Beginner => 15,
+            AiLevel::Intermediate => 5,
+            AiLevel::Advanced | AiLevel::Expert => 0,
+        }
+    }
+
+    /// How far below the best score a move may be and still be chosen.
+    fn tolerance(self) -> i32 {
+        match self {
+            AiLevel::Novice => 150,
+            AiLevel::Beginner => 60,
+            AiLevel::Intermediate => 20,
+            AiLevel::Advanced | AiLevel::Expert => 0,
+        }
+    }
+}
+
+impl fmt::Display for AiLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for AiLevel {
+    type Err = String;
+
+    /// Parses a level name such as `novice`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AiLevel::ALL
+            .into_iter()
+            .find(|level| level.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown AI level '{}'", s))
+    }
+}
+
+/// A computer opponent playing at a fixed [`AiLevel`].
+#[derive(Debug, Clone)]
+pub struct AiPlayer {
+    level: AiLevel,
+    weights: EvalWeights,
+    rng: Rng,
+}
+
+impl AiPlayer {
+    /// An opponent whose mistakes are drawn from `seed`; the same seed
+    /// makes the same choices.
+    pub fn new(level: AiLevel, seed: u64) -> AiPlayer {
+        AiPlayer {
+            level,
+            weights: EvalWeights::default(),
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// The same opponent scoring positions with `weights`.
+    pub fn with_weights(self, weights: EvalWeights) -> AiPlayer {
+        AiPlayer { weights, ..self }
+    }
+
+    pub fn level(&self) -> AiLevel {
+        self.level
+    }
+
+    /// Chooses a move for the side to move, or `None` if the game is over.
+    pub fn choose_move(&mut self, position: &Position) -> Option<Move> {
+        let limit = match self.level {
+            AiLevel::Expert => SearchLimit::Time { time_ms: MOVE_TIME_MS },
+            level => SearchLimit::DepthAndTime {
+                depth: level.depth(),
+                time_ms: MOVE_TIME_MS,
+            },
+        };
+        // Only the best move is wanted, which a plain search finds faster
+        if self.level.blunder_percent() == 0 && self.level.tolerance() == 0 {
+            return Searcher::with_weights(self.weights).search(position, limit).best_move;
+        }
+
+        let scored = Searcher::with_weights(self.weights).score_moves(position, limit);
+        let (_, best_score) = scored.first()?;
+
+        if self.rng.below(100) < self.level.blunder_percent() {
+            let (mv, _) = &scored[self.rng.below(scored.len())];
+            return Some(mv.clone());
+        }
+
+        let candidates = scored
+            .iter()
+            .take_while(|(_, score)| *score >= best_score - self.level.tolerance())
+            .count();
+        let (mv, _) = &scored[self.rng.below(candidates)];
+        Some(mv.clone())
+    }
+}
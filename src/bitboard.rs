@@ -0,0 +1,145 @@
+use std::ops::{BitAnd, BitOr, Not};
+
+use crate::board::MAX_BOARD_SIZE;
+
+const WORDS: usize = MAX_BOARD_SIZE * MAX_BOARD_SIZE / 64;
+
+/// A set of squares, one bit per square index. A 16×16 board needs 256
+/// bits, so the set is four `u64` words; on the standard board only the
+/// first word is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub(crate) struct Bitboard([u64; WORDS]);
+
+impl Bitboard {
+    pub(crate) const EMPTY: Bitboard = Bitboard([0; WORDS]);
+
+    /// The squares `0..len`.
+    #[inline]
+    pub(crate) fn first(len: usize) -> Bitboard {
+        let mut bits = Bitboard::EMPTY;
+        for (i, word) in bits.0.iter_mut().enumerate() {
+            let start = i * 64;
+            *word = match len.saturating_sub(start) {
+                0 => 0,
+                n if n >= 64 => u64::MAX,
+                n => (1 << n) - 1,
+            };
+        }
+        bits
+    }
+
+    /// The set of squares `0..64` given by the bits of `word`.
+    #[inline]
+    pub(crate) fn from_word(word: u64) -> Bitboard {
+        let mut bits = Bitboard::EMPTY;
+        bits.0[0] = word;
+        bits
+    }
+
+    /// The `i`th group of 64 squares, lowest square in the lowest bit.
+    #[inline]
+    pub(crate) fn word(self, i: usize) -> u64 {
+        self.0[i]
+    }
+
+    #[inline]
+    pub(crate) fn contains(self, square: usize) -> bool {
+        self.0[square / 64] & (1 << (square % 64)) != 0
+    }
+
+    #[inline]
+    pub(crate) fn insert(&mut self, square: usize) {
+        self.0[square / 64] |= 1 << (square % 64);
+    }
+
+    #[inline]
+    pub(crate) fn remove(&mut self, square: usize) {
+        self.0[square / 64] &= !(1 << (square % 64));
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    #[inline]
+    pub(crate) fn count(self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// The lowest square in the set.
+    #[inline]
+    pub(crate) fn lowest(self) -> Option<usize> {
+        self.0
+            .iter()
+            .enumerate()
+            .find(|(_, &word)| word != 0)
+            .map(|(i, word)| i * 64 + word.trailing_zeros() as usize)
+    }
+
+    /// Squares in the set, lowest first. Once it has run out it keeps
+    /// returning `None`.
+    #[inline]
+    pub(crate) fn squares(self) -> impl Iterator<Item = usize> {
+        let mut words = self.0;
+        let mut i = 0;
+        std::iter::from_fn(move || {
+            while i < WORDS && words[i] == 0 {
+                i += 1;
+            }
+            if i == WORDS {
+                return None;
+            }
+            let bit = words[i].trailing_zeros() as usize;
+            words[i] &= words[i] - 1;
+            Some(i * 64 + bit)
+        })
+    }
+
+    /// Every square moved `n` indices up, `0 < n < 64`. Bits shifted past
+    /// the last word are dropped.
+    #[inline]
+    pub(crate) fn shift_up(self, n: u32) -> Bitboard {
+        Bitboard(std::array::from_fn(|i| {
+            let carry = if i > 0 { self.0[i - 1] >> (64 - n) } else { 0 };
+            self.0[i] << n | carry
+        }))
+    }
+
+    /// Every square moved `n` indices down, `0 < n < 64`. Bits shifted below
+    /// square 0 are dropped.
+    #[inline]
+    pub(crate) fn shift_down(self, n: u32) -> Bitboard {
+        Bitboard(std::array::from_fn(|i| {
+            let carry = if i + 1 < WORDS { self.0[i + 1] << (64 - n) } else { 0 };
+            self.0[i] >> n | carry
+        }))
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    #[inline]
+    fn bitand(self, other: Bitboard) -> Bitboard {
+        Bitboard(std::array::from_fn(|i| self.0[i] & other.0[i]))
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    #[inline]
+    fn bitor(self, other: Bitboard) -> Bitboard {
+        Bitboard(std::array::from_fn(|i| self.0[i] | other.0[i]))
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    #[inline]
+    fn not(self) -> Bitboard {
+        Bitboard(self.0.map(|word| !word))
+    }
+}
@@ -0,0 +1,244 @@
+use crate::bitboard::Bitboard;
+use crate::Player;
+
+/// Largest supported number of files or ranks.
+pub const MAX_BOARD_SIZE: usize = 16;
+
+/// The kinds of piece a player can have on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PieceKind {
+    /// An ordinary piece.
+    Soldier,
+    /// The leader, when the rules give each side one. It is captured only
+    /// when enclosed on all four sides and cannot be jumped.
+    Dux,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Square {
+    #[default]
+    Empty,
+    Piece(Player, PieceKind),
+}
+
+impl Square {
+    /// The player whose piece stands on this square.
+    pub fn owner(self) -> Option<Player> {
+        match self {
+            Square::Empty => None,
+            Square::Piece(player, _) => Some(player),
+        }
+    }
+
+    pub fn is_dux(self) -> bool {
+        matches!(self, Square::Piece(_, PieceKind::Dux))
+    }
+}
+
+/// A rectangular board of `width` files by `height` ranks. Squares are
+/// indexed `row * width + col`, with A1 at index 0.
+///
+/// Alongside the squares the board keeps a bitboard of each player's pieces
+/// and of the duxes, so that move, capture and mobility checks can work on
+/// every square at once with shifts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    squares: [Square; MAX_BOARD_SIZE * MAX_BOARD_SIZE],
+    light: Bitboard,
+    dark: Bitboard,
+    duxes: Bitboard,
+    // Every square of the board, and those not on the A file or on the
+    // last file, for masking shifts
+    on_board: Bitboard,
+    not_first_file: Bitboard,
+    not_last_file: Bitboard,
+}
+
+impl Board {
+    /// An empty board.
+    pub fn new(width: usize, height: usize) -> Board {
+        let on_board = Bitboard::first(width * height);
+        let mut not_first_file = on_board;
+        let mut not_last_file = on_board;
+        for row in 0..height {
+            not_first_file.remove(row * width);
+            not_last_file.remove(row * width + width - 1);
+        }
+        Board {
+            width,
+            height,
+            squares: [Square::Empty; MAX_BOARD_SIZE * MAX_BOARD_SIZE],
+            light: Bitboard::EMPTY,
+            dark: Bitboard::EMPTY,
+            duxes: Bitboard::EMPTY,
+            on_board,
+            not_first_file,
+            not_last_file,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of squares on the board.
+    pub fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Square {
+        self.squares[index]
+    }
+
+    pub fn set(&mut self, index: usize, square: Square) {
+        self.squares[index] = square;
+        self.light.remove(index);
+        self.dark.remove(index);
+        self.duxes.remove(index);
+        if let Square::Piece(player, kind) = square {
+            match player {
+                Player::Light => self.light.insert(index),
+                Player::Dark => self.dark.insert(index),
+            }
+            if kind == PieceKind::Dux {
+                self.duxes.insert(index);
+            }
+        }
+    }
+
+    pub fn squares(&self) -> &[Square] {
+        &self.squares[..self.len()]
+    }
+
+    /// `player`'s pieces, dux included.
+    #[inline]
+    pub(crate) fn pieces(&self, player: Player) -> Bitboard {
+        match player {
+            Player::Light => self.light,
+            Player::Dark => self.dark,
+        }
+    }
+
+    /// The duxes of both players.
+    #[inline]
+    pub(crate) fn duxes(&self) -> Bitboard {
+        self.duxes
+    }
+
+    #[inline]
+    pub(crate) fn empty_squares(&self) -> Bitboard {
+        self.on_board & !(self.light | self.dark)
+    }
+
+    /// Squares on the edge of the board, corners included.
+    pub(crate) fn edge_squares(&self) -> Bitboard {
+        let on = self.on_board;
+        let interior = self.shift(on, 1, 0) & self.shift(on, -1, 0) & self.shift(on, 0, 1) & self.shift(on, 0, -1);
+        on & !interior
+    }
+
+    /// Squares missing a neighbour both along their rank and along their
+    /// file.
+    pub(crate) fn corner_squares(&self) -> Bitboard {
+        let on = self.on_board;
+        let inside_rank = self.shift(on, 0, 1) & self.shift(on, 0, -1);
+        let inside_file = self.shift(on, 1, 0) & self.shift(on, -1, 0);
+        on & !inside_rank & !inside_file
+    }
+
+    /// Every square of `bits` moved `dr` ranks and `dc` files, one of which
+    /// is ±1 and the other 0. Squares that would leave the board are
+    /// dropped.
+    #[inline]
+    pub(crate) fn shift(&self, bits: Bitboard, dr: i32, dc: i32) -> Bitboard {
+        // Boards of up to 64 squares, the standard one included, fit in the
+        // first word
+        let small = self.len() <= 64;
+        match (dr, dc) {
+            (1, 0) if small => Bitboard::from_word(bits.word(0) << self.width & self.on_board.word(0)),
+            (-1, 0) if small => Bitboard::from_word(bits.word(0) >> self.width),
+            (0, 1) if small => Bitboard::from_word((bits.word(0) & self.not_last_file.word(0)) << 1),
+            (0, -1) if small => Bitboard::from_word((bits.word(0) & self.not_first_file.word(0)) >> 1),
+            (1, 0) => bits.shift_up(self.width as u32) & self.on_board,
+            (-1, 0) => bits.shift_down(self.width as u32),
+            (0, 1) => (bits & self.not_last_file).shift_up(1),
+            (0, -1) => (bits & self.not_first_file).shift_down(1),
+            _ => panic!("shift by ({}, {}) is not a single orthogonal step", dr, dc),
+        }
+    }
+
+    pub fn index(&self, row: usize, col: usize) -> usize {
+        row * self.width + col
+    }
+
+    /// The `(row, col)` of a square, with row 0 being rank 1.
+    pub fn coords(&self, index: usize) -> (usize, usize) {
+        (index / self.width, index % self.width)
+    }
+
+    /// The square `dr` ranks and `dc` files away from `index`, which the
+    /// caller knows to be on the board, e.g. from a shifted bitboard.
+    #[inline]
+    pub(crate) fn step(&self, index: usize, dr: i32, dc: i32) -> usize {
+        (index as isize + dr as isize * self.width as isize + dc as isize) as usize
+    }
+
+    /// The square `dr` ranks and `dc` files away from `index`, if it is on
+    /// the board.
+    pub fn offset(&self, index: usize, dr: i32, dc: i32) -> Option<usize> {
+        let (row, col) = self.coords(index);
+        let row = row as i32 + dr;
+        let col = col as i32 + dc;
+        if (0..self.height as i32).contains(&row) && (0..self.width as i32).contains(&col) {
+            Some(self.index(row as usize, col as usize))
+        } else {
+            None
+        }
+    }
+
+    /// How many squares apart `from` and `to` are, if they share a rank or
+    /// file.
+    pub fn orthogonal_distance(&self, from: usize, to: usize) -> Option<usize> {
+        let (from_row, from_col) = self.coords(from);
+        let (to_row, to_col) = self.coords(to);
+        if from_row == to_row {
+            Some(from_col.abs_diff(to_col))
+        } else if from_col == to_col {
+            Some(from_row.abs_diff(to_row))
+        } else {
+            None
+        }
+    }
+
+    /// The algebraic name of a square, e.g. `A1` for index 0.
+    pub fn square_name(&self, index: usize) -> String {
+        let (row, col) = self.coords(index);
+        format!("{}{}", (b'A' + col as u8) as char, row + 1)
+    }
+
+    /// Parses an algebraic square name such as `C4` or `c4`.
+    pub fn parse_square(&self, name: &str) -> Option<usize> {
+        let mut chars = name.chars();
+        let file = chars.next()?.to_ascii_uppercase();
+        let rank: usize = chars.as_str().parse().ok()?;
+        if !file.is_ascii_uppercase() {
+            return None;
+        }
+        let col = (file as u8 - b'A') as usize;
+        if col >= self.width || rank == 0 || rank > self.height {
+            return None;
+        }
+        Some(self.index(rank - 1, col))
+    }
+}
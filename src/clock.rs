@@ -0,0 +1,204 @@
+//! Chess-style game clock.
+//!
+//! The clock never reads the time itself: every call takes the current time
+//! in milliseconds, so it behaves the same in the browser, in tests and when
+//! replaying logged games. A turn runs from one press of the clock to the
+//! next, so a jump chain, being a single move, is timed as one turn.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Player;
+
+/// How much thinking time each player gets. Deserializes from e.g.
+/// `{ kind: "fischer", base_ms: 180000, increment_ms: 2000 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimeControl {
+    /// A fixed amount of time for the whole game.
+    SuddenDeath { base_ms: u64 },
+    /// `increment_ms` is added to the player's time after each of their
+    /// turns.
+    Fischer { base_ms: u64, increment_ms: u64 },
+    /// The first `delay_ms` of each turn are free; only time beyond the
+    /// delay is taken off the player's time.
+    Bronstein { base_ms: u64, delay_ms: u64 },
+    /// Once the base time is used up, each turn must be made within
+    /// `period_ms`. A turn that runs over uses up a period and starts the
+    /// next; running over the last one loses.
+    ByoYomi { base_ms: u64, period_ms: u64, periods: u32 },
+}
+
+impl TimeControl {
+    fn base_ms(self) -> u64 {
+        match self {
+            TimeControl::SuddenDeath { base_ms }
+            | TimeControl::Fischer { base_ms, .. }
+            | TimeControl::Bronstein { base_ms, .. }
+            | TimeControl::ByoYomi { base_ms, .. } => base_ms,
+        }
+    }
+
+    fn periods(self) -> u32 {
+        match self {
+            TimeControl::ByoYomi { periods, .. } => periods,
+            _ => 0,
+        }
+    }
+}
+
+/// One player's time, as shown on the clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PlayerTime {
+    /// Time the player can still use on this turn before their flag falls.
+    pub remaining_ms: u64,
+    /// Base time left, not counting any delay or byo-yomi periods.
+    pub main_ms: u64,
+    /// Byo-yomi periods left.
+    pub periods: u32,
+}
+
+/// Both players' time at one moment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ClockState {
+    pub light: PlayerTime,
+    pub dark: PlayerTime,
+    /// The player whose time is running, if any.
+    pub running: Option<Player>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Turn {
+    player: Player,
+    started_ms: u64,
+}
+
+/// A clock for two players. It is stopped until [`Clock::start`] or the
+/// first [`Clock::press`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clock {
+    control: TimeControl,
+    // Indexed by `player_index`
+    main_ms: [u64; 2],
+    periods: [u32; 2],
+    turn: Option<Turn>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Clock {
+        Clock {
+            control,
+            main_ms: [control.base_ms(); 2],
+            periods: [control.periods(); 2],
+            turn: None,
+        }
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    /// The player whose time is running.
+    pub fn running(&self) -> Option<Player> {
+        self.turn.map(|turn| turn.player)
+    }
+
+    /// Starts `player`'s turn at `now_ms`. Any turn already running is
+    /// abandoned without charging it.
+    pub fn start(&mut self, player: Player, now_ms: u64) {
+        self.turn = Some(Turn {
+            player,
+            started_ms: now_ms,
+        });
+    }
+
+    /// Stops the clock without charging the running turn.
+    pub fn stop(&mut self) {
+        self.turn = None;
+    }
+
+    /// Ends the running turn at `now_ms`, charging its time, and starts
+    /// `next`'s turn, which may be the same player's again. Returns the
+    /// player whose flag fell instead if the turn took too long; the clock
+    /// then stops. With the clock stopped this only starts `next`'s turn.
+    pub fn press(&mut self, next: Player, now_ms: u64) -> Result<(), Player> {
+        if let Some(turn) = self.turn {
+            let elapsed = now_ms.saturating_sub(turn.started_ms);
+            if elapsed >= self.available_ms(turn.player) {
+                self.turn = None;
+                return Err(turn.player);
+            }
+            self.charge(turn.player, elapsed);
+        }
+        self.start(next, now_ms);
+        Ok(())
+    }
+
+    /// The player whose flag has fallen by `now_ms`, if any.
+    pub fn flagged(&self, now_ms: u64) -> Option<Player> {
+        self.turn
+            .filter(|turn| now_ms.saturating_sub(turn.started_ms) >= self.available_ms(turn.player))
+            .map(|turn| turn.player)
+    }
+
+    /// Both players' time at `now_ms`.
+    pub fn state(&self, now_ms: u64) -> ClockState {
+        ClockState {
+            light: self.player_time(Player::Light, now_ms),
+            dark: self.player_time(Player::Dark, now_ms),
+            running: self.running(),
+        }
+    }
+
+    fn player_time(&self, player: Player, now_ms: u64) -> PlayerTime {
+        let elapsed = match self.turn {
+            Some(turn) if turn.player == player => now_ms.saturating_sub(turn.started_ms),
+            _ => 0,
+        };
+        let free_ms = match self.control {
+            TimeControl::Bronstein { delay_ms, .. } => delay_ms,
+            _ => 0,
+        };
+        let main_ms = self.main_ms[player_index(player)];
+        PlayerTime {
+            remaining_ms: self.available_ms(player).saturating_sub(elapsed),
+            main_ms: main_ms.saturating_sub(elapsed.saturating_sub(free_ms)),
+            periods: self.periods[player_index(player)],
+        }
+    }
+
+    /// Time `player` can use on a turn before their flag falls.
+    fn available_ms(&self, player: Player) -> u64 {
+        let i = player_index(player);
+        match self.control {
+            TimeControl::SuddenDeath { .. } | TimeControl::Fischer { .. } => self.main_ms[i],
+            TimeControl::Bronstein { delay_ms, .. } => self.main_ms[i] + delay_ms,
+            TimeControl::ByoYomi { period_ms, .. } => self.main_ms[i] + period_ms * u64::from(self.periods[i]),
+        }
+    }
+
+    /// Takes a turn of `elapsed` milliseconds, short of the flag, off
+    /// `player`'s time.
+    fn charge(&mut self, player: Player, elapsed: u64) {
+        let i = player_index(player);
+        match self.control {
+            TimeControl::SuddenDeath { .. } => self.main_ms[i] -= elapsed,
+            TimeControl::Fischer { increment_ms, .. } => self.main_ms[i] = self.main_ms[i] - elapsed + increment_ms,
+            TimeControl::Bronstein { delay_ms, .. } => self.main_ms[i] -= elapsed.saturating_sub(delay_ms),
+            TimeControl::ByoYomi { period_ms, .. } => {
+                let over = elapsed.saturating_sub(self.main_ms[i]);
+                self.main_ms[i] = self.main_ms[i].saturating_sub(elapsed);
+                // Every whole period the turn ran past is used up
+                if over > 0 {
+                    self.periods[i] -= (over / period_ms) as u32;
+                }
+            }
+        }
+    }
+}
+
+fn player_index(player: Player) -> usize {
+    match player {
+        Player::Light => 0,
+        Player::Dark => 1,
+    }
+}
@@ -0,0 +1,163 @@
+//! Static evaluation of positions, shared by the engines and the hint UI.
+//!
+//! Each term is counted for both players and the difference, multiplied by
+//! its weight in [`EvalWeights`], is added to the score.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bitboard::Bitboard;
+use crate::position::{GamePhase, Position, DIRECTIONS};
+use crate::rules::{CustodialCapture, Movement};
+use crate::Player;
+
+/// Weights of the evaluation terms, per piece or per move. They serialize
+/// field by field so that tuned sets can be stored and loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalWeights {
+    /// Each piece on the board or still to be placed.
+    pub material: i32,
+    /// Each step or jump a player's pieces could make.
+    pub mobility: i32,
+    /// Each piece the opponent could capture by custodianship with one move.
+    /// Counts against the threatened side.
+    pub threatened: i32,
+    /// Each piece on an edge, where it can only be flanked along the edge.
+    pub edge: i32,
+    /// Each piece in a corner, where it cannot be flanked at all.
+    pub corner: i32,
+    /// Each capturing jump a player could make.
+    pub jump_opportunities: i32,
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        EvalWeights {
+            material: 100,
+            mobility: 2,
+            threatened: 30,
+            edge: 4,
+            corner: 10,
+            jump_opportunities: 20,
+        }
+    }
+}
+
+/// Scores `position` for `player`: positive is good for them. Does not
+/// detect won or lost positions; see [`Game`](crate::Game) for those.
+pub fn evaluate_position(position: &Position, player: Player, weights: &EvalWeights) -> i32 {
+    let terms = |player: Player| {
+        let (edge, corner) = edge_and_corner_pieces(position, player);
+        material(position, player) * weights.material
+            + position.mobility(player) as i32 * weights.mobility
+            - threatened_pieces(position, player) * weights.threatened
+            + edge * weights.edge
+            + corner * weights.corner
+            + jump_opportunities(position, player) * weights.jump_opportunities
+    };
+    terms(player) - terms(player.opponent())
+}
+
+fn material(position: &Position, player: Player) -> i32 {
+    let in_hand = position.rules().pieces_per_side - position.pieces_placed(player);
+    (position.piece_count(player) + usize::from(in_hand)) as i32
+}
+
+/// `player`'s pieces on an edge but not in a corner, and in a corner.
+fn edge_and_corner_pieces(position: &Position, player: Player) -> (i32, i32) {
+    let board = position.board();
+    let pieces = board.pieces(player);
+    let corners = board.corner_squares();
+    let edge = pieces & board.edge_squares() & !corners;
+    (edge.count() as i32, (pieces & corners).count() as i32)
+}
+
+fn jump_opportunities(position: &Position, player: Player) -> i32 {
+    if !position.rules().jumps_capture || position.phase() != GamePhase::Movement {
+        return 0;
+    }
+    DIRECTIONS
+        .iter()
+        .map(|&(dr, dc)| jump_landings(position, player, dr, dc).count() as i32)
+        .sum()
+}
+
+/// Empty squares `player`'s pieces could land on by jumping in the
+/// direction `(dr, dc)`.
+fn jump_landings(position: &Position, player: Player, dr: i32, dc: i32) -> Bitboard {
+    let board = position.board();
+    let jumpable = board.pieces(player.opponent()) & !board.duxes();
+    board.shift(board.shift(board.pieces(player), dr, dc) & jumpable, dr, dc) & board.empty_squares()
+}
+
+fn threatened_pieces(position: &Position, player: Player) -> i32 {
+    threatened_squares(position, player).count() as i32
+}
+
+/// `player`'s pieces the opponent could capture by custodianship with their
+/// next move, judged from the squares the opponent can reach.
+pub(crate) fn threatened_squares(position: &Position, player: Player) -> Bitboard {
+    if position.rules().custodial == CustodialCapture::Disabled {
+        return Bitboard::EMPTY;
+    }
+    let board = position.board();
+    let enemy = player.opponent();
+    let empty = board.empty_squares();
+
+    // Squares an enemy piece can step or slide to, or place a piece on, and
+    // squares it can land on by jumping in each direction
+    let (steps, landings) = match position.phase() {
+        GamePhase::Placement if position.pieces_placed(enemy) < position.rules().pieces_per_side => {
+            (empty, [Bitboard::EMPTY; 4])
+        }
+        GamePhase::Placement => (Bitboard::EMPTY, [Bitboard::EMPTY; 4]),
+        GamePhase::Movement => {
+            let mut steps = Bitboard::EMPTY;
+            for (dr, dc) in DIRECTIONS {
+                let mut reached = board.shift(board.pieces(enemy), dr, dc) & empty;
+                while !reached.is_empty() {
+                    steps = steps | reached;
+                    if position.rules().movement == Movement::Step {
+                        break;
+                    }
+                    reached = board.shift(reached, dr, dc) & empty;
+                }
+            }
+            (steps, DIRECTIONS.map(|(dr, dc)| jump_landings(position, enemy, dr, dc)))
+        }
+    };
+    let reachable = landings.iter().fold(steps, |reach, &landing| reach | landing);
+
+    let pieces = board.pieces(player);
+    let enemies = board.pieces(enemy);
+    let mut soldiers_at_risk = Bitboard::EMPTY;
+    let mut enemy_sides = [Bitboard::EMPTY; 4];
+    for (d, &(dr, dc)) in DIRECTIONS.iter().enumerate() {
+        // Squares with an enemy on the side opposite the direction
+        enemy_sides[d] = board.shift(enemies, dr, dc);
+        // An enemy arriving on the far side must not be the one already
+        // flanking, which could only get there by jumping the piece itself
+        let others_reach = landings
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != d)
+            .fold(steps, |reach, (_, &landing)| reach | landing);
+        let far_side_reachable = board.shift(others_reach, -dr, -dc);
+        soldiers_at_risk = soldiers_at_risk | (enemy_sides[d] & far_side_reachable);
+    }
+    soldiers_at_risk = soldiers_at_risk & pieces & !board.duxes();
+
+    // A dux with enemies on three sides and an empty fourth side an enemy
+    // can reach
+    let mut duxes_at_risk = Bitboard::EMPTY;
+    for (gap, &(dr, dc)) in DIRECTIONS.iter().enumerate() {
+        let closing = board.shift(reachable, -dr, -dc);
+        let enclosed = (0..4)
+            .filter(|&side| side != gap ^ 1)
+            .fold(closing, |bits, side| bits & enemy_sides[side]);
+        duxes_at_risk = duxes_at_risk | enclosed;
+    }
+    duxes_at_risk = duxes_at_risk & pieces & board.duxes();
+
+    soldiers_at_risk | duxes_at_risk
+}
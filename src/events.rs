@@ -0,0 +1,157 @@
+//! Events describing what a move or a player's decision did, in the order
+//! it happened, for animating moves and writing logs without diffing boards.
+
+use serde::Serialize;
+
+use crate::board::{Board, PieceKind, Square};
+use crate::game::{Game, GameResult};
+use crate::moves::Move;
+use crate::position::{GamePhase, Position, DIRECTIONS};
+use crate::Player;
+
+/// Something that happened during a move. Serializes for JavaScript with a
+/// `type` field, e.g. `{ type: "piece_moved", player: "Light", from: 9,
+/// to: 10 }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    PiecePlaced { player: Player, square: usize, dux: bool },
+    /// A step, or a slide under rook movement.
+    PieceMoved { player: Player, from: usize, to: usize },
+    /// A piece begins jumping. Every jump, even a single one, is a chain
+    /// that starts and ends.
+    JumpChainStarted { player: Player, from: usize },
+    /// One hop of a jump chain. `captured` says whether the piece jumped
+    /// over was removed.
+    PieceJumped {
+        player: Player,
+        from: usize,
+        over: usize,
+        to: usize,
+        captured: bool,
+    },
+    JumpChainEnded { player: Player, at: usize, jumps: usize },
+    /// Pieces removed by custodianship after the piece arrived.
+    PiecesCaptured { captures: Vec<CapturedPiece> },
+    PhaseChanged { phase: GamePhase },
+    /// `player` is now to move.
+    TurnPassed { player: Player },
+    /// `player` offered their opponent a draw.
+    DrawOffered { player: Player },
+    /// `player` declined a draw offer, by answering it or by moving.
+    DrawDeclined { player: Player },
+    GameOver { result: GameResult },
+}
+
+/// A piece removed by custodianship and the pieces that enclosed it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CapturedPiece {
+    pub square: usize,
+    pub player: Player,
+    pub dux: bool,
+    pub capturers: Vec<usize>,
+}
+
+/// The events of `mv`, played from `before` and capturing `captured`, with
+/// `game` the game after the move.
+pub(crate) fn move_events(before: &Position, mv: &Move, captured: &[usize], game: &Game) -> Vec<GameEvent> {
+    let player = before.current_player();
+    let rules = before.rules();
+    let mut events = Vec::new();
+
+    // The piece as it stood on its last square, whether or not it survived
+    let moving_piece = match *mv {
+        Move::Place(_) => {
+            let dux = rules.dux && before.pieces_placed(player) == 0;
+            Square::Piece(player, if dux { PieceKind::Dux } else { PieceKind::Soldier })
+        }
+        _ => before.board().get(mv.from().expect("only placements have no origin")),
+    };
+
+    match *mv {
+        Move::Place(square) => events.push(GameEvent::PiecePlaced {
+            player,
+            square,
+            dux: moving_piece.is_dux(),
+        }),
+        Move::Step(from, to) => events.push(GameEvent::PieceMoved { player, from, to }),
+        Move::Jump(..) | Move::MultiJump(_) => {
+            let path = mv.path();
+            events.push(GameEvent::JumpChainStarted { player, from: path[0] });
+            for hop in path.windows(2) {
+                events.push(GameEvent::PieceJumped {
+                    player,
+                    from: hop[0],
+                    over: (hop[0] + hop[1]) / 2,
+                    to: hop[1],
+                    captured: rules.jumps_capture,
+                });
+            }
+            events.push(GameEvent::JumpChainEnded {
+                player,
+                at: mv.to(),
+                jumps: path.len() - 1,
+            });
+        }
+    }
+
+    // Jumps that capture end the turn without custodial captures
+    let custodial = !(mv.is_jump() && rules.jumps_capture);
+    if custodial && !captured.is_empty() {
+        // The board as it was just before the captured pieces were removed
+        let after = game.position();
+        let mut board = after.board().clone();
+        for &square in captured {
+            let piece = if square == mv.to() { moving_piece } else { before.board().get(square) };
+            board.set(square, piece);
+        }
+        let captures = captured
+            .iter()
+            .map(|&square| CapturedPiece {
+                square,
+                player: board.get(square).owner().expect("captured squares hold pieces"),
+                dux: board.get(square).is_dux(),
+                capturers: capturers(&board, square),
+            })
+            .collect();
+        events.push(GameEvent::PiecesCaptured { captures });
+    }
+
+    let after = game.position();
+    if after.phase() != before.phase() {
+        events.push(GameEvent::PhaseChanged { phase: after.phase() });
+    }
+    if let Some(result) = game.result() {
+        events.push(GameEvent::GameOver { result });
+    } else if after.current_player() != player {
+        events.push(GameEvent::TurnPassed {
+            player: after.current_player(),
+        });
+    }
+    events
+}
+
+/// The enemy pieces enclosing the piece on `square`: every pair on opposite
+/// sides of a soldier, or all four neighbours of a dux.
+fn capturers(board: &Board, square: usize) -> Vec<usize> {
+    let piece = board.get(square);
+    let enemy = piece.owner().map(Player::opponent);
+    let [below, above, left, right] = DIRECTIONS.map(|(dr, dc)| {
+        board
+            .offset(square, dr, dc)
+            .filter(|&neighbour| enemy.is_some() && board.get(neighbour).owner() == enemy)
+    });
+
+    let mut capturers = Vec::new();
+    if piece.is_dux() {
+        capturers.extend([below, above, left, right].into_iter().flatten());
+    } else {
+        for (a, b) in [(below, above), (left, right)] {
+            if let (Some(a), Some(b)) = (a, b) {
+                capturers.extend([a, b]);
+            }
+        }
+    }
+    capturers.sort_unstable();
+    capturers
+}
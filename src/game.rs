@@ -0,0 +1,497 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::moves::{Move, MoveError};
+use crate::position::{GamePhase, Position};
+use crate::rules::RuleSet;
+use crate::Player;
+
+/// A move that has been played, as recorded in the game history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlayedMove {
+    pub player: Player,
+    pub mv: Move,
+    /// Squares of the pieces the move removed, by jump or by custodial capture.
+    pub captured: Vec<usize>,
+}
+
+/// How a game ended. Serializes for JavaScript as e.g.
+/// `{ kind: "win", winner: "Light", reason: "elimination" }` or
+/// `{ kind: "draw", reason: "repetition" }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GameResult {
+    Win { winner: Player, reason: WinReason },
+    Draw { reason: DrawReason },
+}
+
+impl GameResult {
+    /// The reason as a lower-case identifier, e.g. `"elimination"`.
+    pub fn reason_str(self) -> &'static str {
+        match self {
+            GameResult::Win { reason, .. } => reason.as_str(),
+            GameResult::Draw { reason } => reason.as_str(),
+        }
+    }
+
+    /// The winner, or `None` for a draw.
+    pub fn winner(self) -> Option<Player> {
+        match self {
+            GameResult::Win { winner, .. } => Some(winner),
+            GameResult::Draw { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameResult::Win { winner, reason } => write!(f, "{:?} wins by {}", winner, reason),
+            GameResult::Draw { reason } => write!(f, "draw by {}", reason),
+        }
+    }
+}
+
+/// Why a game was won.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WinReason {
+    /// The loser has no pieces left.
+    Elimination,
+    /// The loser cannot move on their turn.
+    Immobilization,
+    /// The loser's dux was captured.
+    DuxCaptured,
+    /// The loser's dux cannot move on their turn.
+    DuxTrapped,
+    Resignation,
+    /// The loser ran out of time.
+    Timeout,
+}
+
+impl WinReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WinReason::Elimination => "elimination",
+            WinReason::Immobilization => "immobilization",
+            WinReason::DuxCaptured => "dux_captured",
+            WinReason::DuxTrapped => "dux_trapped",
+            WinReason::Resignation => "resignation",
+            WinReason::Timeout => "timeout",
+        }
+    }
+}
+
+impl fmt::Display for WinReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WinReason::Elimination => write!(f, "elimination"),
+            WinReason::Immobilization => write!(f, "immobilization"),
+            WinReason::DuxCaptured => write!(f, "capturing the dux"),
+            WinReason::DuxTrapped => write!(f, "trapping the dux"),
+            WinReason::Resignation => write!(f, "resignation"),
+            WinReason::Timeout => write!(f, "timeout"),
+        }
+    }
+}
+
+/// Why a game ended in a draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawReason {
+    /// The same position, with the same side to move, arose three times.
+    Repetition,
+    /// The movement phase went [`RuleSet::move_limit`] moves without a
+    /// capture.
+    MoveLimit,
+    /// The players agreed to a draw.
+    Agreement,
+}
+
+impl DrawReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DrawReason::Repetition => "repetition",
+            DrawReason::MoveLimit => "move_limit",
+            DrawReason::Agreement => "agreement",
+        }
+    }
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawReason::Repetition => write!(f, "repetition"),
+            DrawReason::MoveLimit => write!(f, "move limit"),
+            DrawReason::Agreement => write!(f, "agreement"),
+        }
+    }
+}
+
+/// Why a resignation or draw offer was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// The game has already finished.
+    GameOver,
+    /// A draw offer is already waiting for an answer.
+    DrawAlreadyOffered,
+    /// There is no draw offer to accept or decline.
+    NoDrawOffer,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::GameOver => write!(f, "the game is over"),
+            GameError::DrawAlreadyOffered => write!(f, "a draw has already been offered"),
+            GameError::NoDrawOffer => write!(f, "no draw has been offered"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// A game of Latrones: a [`Position`] plus the end-of-game state and the
+/// move history used for undo and redo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    position: Position,
+    result: Option<GameResult>,
+    // The player whose draw offer is waiting for an answer
+    draw_offer: Option<Player>,
+    history: Vec<PlayedMove>,
+    // Position before each move in `history`, restored by `undo`
+    previous_positions: Vec<Position>,
+    // Undone moves, most recently undone last
+    redo_stack: Vec<PlayedMove>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game::new()
+    }
+}
+
+impl Game {
+    /// A new game at the start of the placement phase.
+    pub fn new() -> Game {
+        Game::from_position(Position::new())
+    }
+
+    /// A new game under `rules` at the start of the placement phase.
+    pub fn with_rules(rules: RuleSet) -> Game {
+        Game::from_position(Position::with_rules(rules))
+    }
+
+    /// A new game from the standard opening setup, skipping placement.
+    pub fn starting() -> Game {
+        Game::from_position(Position::starting())
+    }
+
+    /// A new game under `rules` from the opening setup, skipping placement.
+    pub fn starting_with_rules(rules: RuleSet) -> Game {
+        Game::from_position(Position::starting_with_rules(rules))
+    }
+
+    /// Starts a game from an arbitrary position. The game may already be over
+    /// if the position is won.
+    pub fn from_position(position: Position) -> Game {
+        let mut game = Game {
+            position,
+            result: None,
+            draw_offer: None,
+            history: Vec::new(),
+            previous_positions: Vec::new(),
+            redo_stack: Vec::new(),
+        };
+        game.check_win_condition();
+        game
+    }
+
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        self.position.rules()
+    }
+
+    pub fn current_player(&self) -> Player {
+        self.position.current_player()
+    }
+
+    pub fn phase(&self) -> GamePhase {
+        self.position.phase()
+    }
+
+    pub fn game_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// How the game ended, or `None` while it goes on.
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.result.and_then(GameResult::winner)
+    }
+
+    /// Why the game was drawn, if it was.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        match self.result {
+            Some(GameResult::Draw { reason }) => Some(reason),
+            _ => None,
+        }
+    }
+
+    /// Every move the side to move can play; empty once the game is over.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.game_over() {
+            return Vec::new();
+        }
+        self.position.legal_moves()
+    }
+
+    /// Plays `mv` for the side to move and returns the squares of the pieces
+    /// it captured. Playing a new move discards any moves that were undone.
+    pub fn apply(&mut self, mv: Move) -> Result<Vec<usize>, MoveError> {
+        let captured = self.play(mv)?;
+        self.redo_stack.clear();
+        Ok(captured)
+    }
+
+    fn play(&mut self, mv: Move) -> Result<Vec<usize>, MoveError> {
+        if self.game_over() {
+            return Err(MoveError::GameOver);
+        }
+
+        let before = self.position.clone();
+        let player = before.current_player();
+        // Moving instead of answering a draw offer declines it
+        if self.draw_offer.is_some_and(|offerer| offerer != player) {
+            self.draw_offer = None;
+        }
+        let captured = self.position.apply(mv.clone())?;
+
+        self.previous_positions.push(before);
+        self.history.push(PlayedMove {
+            player,
+            mv,
+            captured: captured.clone(),
+        });
+        self.check_win_condition();
+        Ok(captured)
+    }
+
+    /// The position the game started from, before any move in the history.
+    pub fn initial_position(&self) -> &Position {
+        self.previous_positions.first().unwrap_or(&self.position)
+    }
+
+    /// Moves played so far, oldest first.
+    pub fn history(&self) -> &[PlayedMove] {
+        &self.history
+    }
+
+    pub fn last_move(&self) -> Option<&PlayedMove> {
+        self.history.last()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty() && !self.ended_off_board()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty() && !self.game_over()
+    }
+
+    /// Whether the game ended by resignation, timeout or agreement rather
+    /// than by a move. Taking back a move cannot undo such a result.
+    fn ended_off_board(&self) -> bool {
+        matches!(
+            self.result,
+            Some(GameResult::Win {
+                reason: WinReason::Resignation | WinReason::Timeout,
+                ..
+            }) | Some(GameResult::Draw {
+                reason: DrawReason::Agreement
+            })
+        )
+    }
+
+    /// Ends the game with a win for `player`'s opponent.
+    pub fn resign(&mut self, player: Player) -> Result<(), GameError> {
+        if self.game_over() {
+            return Err(GameError::GameOver);
+        }
+        self.draw_offer = None;
+        self.result = Some(GameResult::Win {
+            winner: player.opponent(),
+            reason: WinReason::Resignation,
+        });
+        Ok(())
+    }
+
+    /// Ends the game with a win for `player`'s opponent because `player`'s
+    /// flag fell. The [clock](crate::Clock) is kept by the caller.
+    pub fn time_out(&mut self, player: Player) -> Result<(), GameError> {
+        if self.game_over() {
+            return Err(GameError::GameOver);
+        }
+        self.draw_offer = None;
+        self.result = Some(GameResult::Win {
+            winner: player.opponent(),
+            reason: WinReason::Timeout,
+        });
+        Ok(())
+    }
+
+    /// Offers the opponent of `player` a draw. The offer stands until they
+    /// accept it, decline it or play a move.
+    pub fn offer_draw(&mut self, player: Player) -> Result<(), GameError> {
+        if self.game_over() {
+            return Err(GameError::GameOver);
+        }
+        if self.draw_offer.is_some() {
+            return Err(GameError::DrawAlreadyOffered);
+        }
+        self.draw_offer = Some(player);
+        Ok(())
+    }
+
+    /// The player whose draw offer is waiting for an answer.
+    pub fn draw_offer(&self) -> Option<Player> {
+        self.draw_offer
+    }
+
+    /// Accepts the draw offer, drawing the game by agreement.
+    pub fn accept_draw(&mut self) -> Result<(), GameError> {
+        if self.game_over() {
+            return Err(GameError::GameOver);
+        }
+        self.draw_offer.take().ok_or(GameError::NoDrawOffer)?;
+        self.result = Some(GameResult::Draw {
+            reason: DrawReason::Agreement,
+        });
+        Ok(())
+    }
+
+    /// Declines the draw offer; the game goes on.
+    pub fn decline_draw(&mut self) -> Result<(), GameError> {
+        if self.game_over() {
+            return Err(GameError::GameOver);
+        }
+        self.draw_offer.take().ok_or(GameError::NoDrawOffer)?;
+        Ok(())
+    }
+
+    /// Takes back the last move, restoring any pieces it captured. Returns
+    /// false if there is nothing to undo, or if the game was resigned, lost
+    /// on time or agreed drawn.
+    pub fn undo(&mut self) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
+        let (Some(played), Some(before)) = (self.history.pop(), self.previous_positions.pop()) else {
+            return false;
+        };
+
+        self.position = before;
+        self.result = None;
+        self.draw_offer = None;
+        self.redo_stack.push(played);
+        self.check_win_condition();
+        true
+    }
+
+    /// Replays the most recently undone move. Returns false if there is
+    /// nothing to redo or the game is over; the move stays to be redone.
+    pub fn redo(&mut self) -> bool {
+        if self.game_over() {
+            return false;
+        }
+        let Some(played) = self.redo_stack.pop() else {
+            return false;
+        };
+        self.play(played.mv).is_ok()
+    }
+
+    fn check_win_condition(&mut self) {
+        if let Some((winner, reason)) = win(&self.position) {
+            self.result = Some(GameResult::Win { winner, reason });
+        } else if let Some(reason) = self.draw() {
+            self.result = Some(GameResult::Draw { reason });
+        }
+    }
+
+    /// Checks the draw rules against the moves since the last capture.
+    /// Positions before a capture or during placement had other pieces on
+    /// the board or in hand, so they cannot repeat.
+    fn draw(&self) -> Option<DrawReason> {
+        let mut quiet_moves = 0;
+        let mut repetitions = 1;
+        let earlier = self.history.iter().zip(&self.previous_positions).rev();
+        for (played, before) in earlier {
+            if !played.captured.is_empty() || before.phase() != GamePhase::Movement {
+                break;
+            }
+            quiet_moves += 1;
+            if before.hash() == self.position.hash() && *before == self.position {
+                repetitions += 1;
+            }
+        }
+
+        if repetitions >= 3 {
+            Some(DrawReason::Repetition)
+        } else if self.rules().move_limit.is_some_and(|limit| quiet_moves >= usize::from(limit)) {
+            Some(DrawReason::MoveLimit)
+        } else {
+            None
+        }
+    }
+}
+
+/// The player who has won in `position`, or `None` if the game goes on.
+pub(crate) fn winner(position: &Position) -> Option<Player> {
+    win(position).map(|(winner, _)| winner)
+}
+
+/// The player who has won in `position` and how.
+fn win(position: &Position) -> Option<(Player, WinReason)> {
+    // A player whose dux is captured loses, even during placement
+    for player in [Player::Light, Player::Dark] {
+        if position.dux_captured(player) {
+            return Some((player.opponent(), WinReason::DuxCaptured));
+        }
+    }
+
+    if position.phase() != GamePhase::Movement {
+        return None;
+    }
+
+    // Count pieces
+    let light_count = position.piece_count(Player::Light);
+    let dark_count = position.piece_count(Player::Dark);
+
+    // Check if a player has no pieces - the player with pieces left wins
+    if light_count == 0 && dark_count > 0 {
+        return Some((Player::Dark, WinReason::Elimination));
+    }
+    if dark_count == 0 && light_count > 0 {
+        return Some((Player::Light, WinReason::Elimination));
+    }
+
+    // The player to move who cannot move loses, as does one whose dux is
+    // trapped
+    let side = position.current_player();
+    if !position.has_legal_moves() {
+        return Some((side.opponent(), WinReason::Immobilization));
+    }
+    if position.dux_trapped(side) {
+        return Some((side.opponent(), WinReason::DuxTrapped));
+    }
+
+    None
+}
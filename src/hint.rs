@@ -0,0 +1,79 @@
+//! Suggested moves for players who ask for help, with a short reason the
+//! UI can show next to the highlighted move.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::eval::{threatened_squares, EvalWeights};
+use crate::moves::Move;
+use crate::position::Position;
+use crate::search::{SearchLimit, Searcher};
+
+/// Why a suggested move is good, most important first. Serializes for
+/// JavaScript as e.g. `{ kind: "captures", count: 2 }` or
+/// `{ kind: "escapes_threat" }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HintReason {
+    /// The move captures `count` enemy pieces.
+    Captures { count: usize },
+    /// Fewer of the mover's pieces can be captured next turn.
+    EscapesThreat,
+    /// More enemy pieces can be captured by custodianship next turn.
+    SetsUpCapture,
+    /// None of the above; the search simply prefers the move.
+    Positional,
+}
+
+impl HintReason {
+    /// Classifies `mv`, which must be legal in `position`.
+    pub fn for_move(position: &Position, mv: &Move) -> HintReason {
+        let player = position.current_player();
+        let enemy = player.opponent();
+        let mut after = position.clone();
+        let captured = after.play(mv.clone());
+
+        if !captured.is_empty() {
+            HintReason::Captures { count: captured.len() }
+        } else if threatened_squares(&after, player).count() < threatened_squares(position, player).count() {
+            HintReason::EscapesThreat
+        } else if threatened_squares(&after, enemy).count() > threatened_squares(position, enemy).count() {
+            HintReason::SetsUpCapture
+        } else {
+            HintReason::Positional
+        }
+    }
+}
+
+impl fmt::Display for HintReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HintReason::Captures { count: 1 } => write!(f, "captures a piece"),
+            HintReason::Captures { count } => write!(f, "captures {} pieces", count),
+            HintReason::EscapesThreat => write!(f, "escapes a threat"),
+            HintReason::SetsUpCapture => write!(f, "sets up a custodial capture"),
+            HintReason::Positional => write!(f, "improves the position"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Hint {
+    pub mv: Move,
+    pub reason: HintReason,
+    /// The reason as a phrase for display, e.g. `captures 2 pieces`.
+    pub message: String,
+}
+
+/// The best move for the side to move found within `limit`, with the
+/// reason it is good. `None` if the game is over.
+pub fn suggest_move(position: &Position, limit: SearchLimit, weights: EvalWeights) -> Option<Hint> {
+    let mv = Searcher::with_weights(weights).search(position, limit).best_move?;
+    let reason = HintReason::for_move(position, &mv);
+    Some(Hint {
+        mv,
+        reason,
+        message: reason.to_string(),
+    })
+}
@@ -1,623 +1,768 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+mod ai;
+mod bitboard;
+mod board;
+mod clock;
+mod eval;
+mod events;
+mod game;
+mod hint;
+mod mcts;
+mod moves;
+mod notation;
+mod perft;
+mod position;
+mod record;
+mod rng;
+mod rules;
+mod search;
+mod select;
+mod threat;
+mod tt;
+mod zobrist;
+
+pub use ai::{AiLevel, AiPlayer};
+pub use board::{Board, PieceKind, Square, MAX_BOARD_SIZE};
+pub use clock::{Clock, ClockState, PlayerTime, TimeControl};
+pub use eval::{evaluate_position, EvalWeights};
+pub use events::{CapturedPiece, GameEvent};
+pub use game::{DrawReason, Game, GameError, GameResult, PlayedMove, WinReason};
+pub use hint::{suggest_move, Hint, HintReason};
+pub use mcts::{MctsConfig, MctsResult, MctsSearcher};
+pub use moves::{Move, MoveError};
+pub use notation::NotationError;
+pub use position::{GamePhase, Position};
+pub use rules::{CustodialCapture, Movement, MultiJumps, RuleSet, DEFAULT_MOVE_LIMIT};
+pub use record::{GameRecord, RecordError, RecordResult, RecordedMove};
+pub use search::{SearchLimit, SearchResult, Searcher, MAX_DEPTH};
+pub use select::{SelectError, Selection};
+pub use threat::Threat;
+pub use tt::{Bound, TranspositionTable, TtEntry};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(message: &str, error: &JsValue);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[wasm_bindgen]
 pub enum Player {
     Light,
     Dark,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Square {
-    Empty,
-    Piece(Player),
-}
-
-impl Default for Square {
-    fn default() -> Self {
-        Square::Empty
+impl Player {
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::Light => Player::Dark,
+            Player::Dark => Player::Light,
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum GamePhase {
-    Placement,
-    Movement,
-}
-
-#[derive(Debug, Clone)]
+/// Browser-facing wrapper around [`Game`] that turns square clicks into moves.
+#[derive(Debug, Clone, Default)]
 #[wasm_bindgen]
 pub struct GameState {
-    board: [Square; 64],
-    current_player: Player,
-    phase: GamePhase,
-    light_pieces_placed: u8,
-    dark_pieces_placed: u8,
-    selected_square: Option<usize>,
-    must_continue_jumping: bool, // If true, only jump moves are allowed
-    piece_that_captured: Option<usize>, // Track which piece has captured this turn
-    game_over: bool,
-    winner: Option<Player>,
+    game: Game,
+    // The selected piece, then any squares picked along its jump chain
+    selected_path: Vec<usize>,
+    ai_level: AiLevel,
+    eval_weights: EvalWeights,
+    // Events not yet drained, or the function they are passed to instead
+    events: Vec<GameEvent>,
+    event_callback: Option<js_sys::Function>,
+    clock: Option<Clock>,
 }
 
 #[wasm_bindgen]
 impl GameState {
     #[wasm_bindgen(constructor)]
     pub fn new() -> GameState {
-        GameState {
-            board: [Square::Empty; 64],
-            current_player: Player::Light,
-            phase: GamePhase::Placement,
-            light_pieces_placed: 0,
-            dark_pieces_placed: 0,
-            selected_square: None,
-            must_continue_jumping: false,
-            piece_that_captured: None,
-            game_over: false,
-            winner: None,
-        }
+        GameState::from_game(Game::new())
+    }
+
+    /// A new game under the named rule preset, e.g. `"Schadler"`. Throws if
+    /// there is no such preset.
+    pub fn with_variant(name: &str) -> Result<GameState, JsValue> {
+        let rules = RuleSet::preset(name)
+            .ok_or_else(|| JsValue::from_str(&format!("unknown variant '{}'", name)))?;
+        Ok(GameState::from_game(Game::with_rules(rules)))
+    }
+
+    /// A new game under the default rules on a board of `width` files by
+    /// `height` ranks. Throws if the size is not supported.
+    pub fn with_board_size(width: usize, height: usize) -> Result<GameState, JsValue> {
+        let rules = RuleSet::default().with_board_size(width, height);
+        rules.validate().map_err(JsValue::from_str)?;
+        Ok(GameState::from_game(Game::with_rules(rules)))
+    }
+
+    /// Starts a game from a position in the notation produced by
+    /// `to_notation`, under the named rule preset (Schadler if omitted) on a
+    /// board of the size the notation describes. Throws a descriptive error
+    /// if the variant is unknown or the notation is invalid.
+    pub fn from_notation(notation: &str, variant: Option<String>) -> Result<GameState, JsValue> {
+        let rules = match variant {
+            Some(name) => RuleSet::preset(&name)
+                .ok_or_else(|| JsValue::from_str(&format!("unknown variant '{}'", name)))?,
+            None => RuleSet::default(),
+        };
+        let position = Position::from_notation_with_rules(notation, notation::sized_rules(notation, rules))
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(GameState::from_game(Game::from_position(position)))
+    }
+
+    /// The current position in text notation, e.g.
+    /// `D6L/D6L/D6L/D6L/D6L/D6L/D6L/D6L l m 8 8`.
+    pub fn to_notation(&self) -> String {
+        self.game.position().to_notation()
+    }
+
+    /// Zobrist hash of the current position as 16 hex digits. Equal
+    /// positions have equal hashes, so repetitions can be spotted by
+    /// comparing them.
+    pub fn position_hash(&self) -> String {
+        format!("{:016x}", self.game.position().hash())
+    }
+
+    /// Replays a game record. Throws an error naming the first move that
+    /// does not follow the rules.
+    pub fn from_record(record: &str) -> Result<GameState, JsValue> {
+        let game = GameRecord::parse(record)
+            .and_then(|record| record.replay())
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(GameState::from_game(game))
     }
-    
+
+    /// The game so far as a record with placeholder headers.
+    pub fn to_record(&self) -> String {
+        GameRecord::from_game(&self.game).to_string()
+    }
+
+    /// Name of the rule preset in play, or `"Custom"`.
+    #[wasm_bindgen(getter)]
+    pub fn variant(&self) -> String {
+        self.game.rules().name().unwrap_or("Custom").to_string()
+    }
+
+    /// Number of files; squares are indexed `row * width + col`.
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.game.rules().width
+    }
+
+    /// Number of ranks.
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.game.rules().height
+    }
+
     #[wasm_bindgen(getter)]
     pub fn current_player(&self) -> Player {
-        self.current_player
+        self.game.current_player()
     }
-    
+
     #[wasm_bindgen(getter)]
     pub fn game_over(&self) -> bool {
-        self.game_over
+        self.game.game_over()
     }
-    
+
     #[wasm_bindgen(getter)]
     pub fn winner(&self) -> Option<Player> {
-        self.winner
+        self.game.winner()
     }
-    
+
+    /// Why the game was drawn: `"repetition"` when a position arose for the
+    /// third time, `"move_limit"` after too many moves without a capture,
+    /// `"agreement"`, or `undefined`.
     #[wasm_bindgen(getter)]
-    pub fn phase(&self) -> String {
-        match self.phase {
-            GamePhase::Placement => "placement".to_string(),
-            GamePhase::Movement => "movement".to_string(),
+    pub fn draw_reason(&self) -> Option<String> {
+        self.game.draw_reason().map(|reason| reason.as_str().to_string())
+    }
+
+    /// How the game ended, or `null` while it goes on: `{ kind: "win",
+    /// winner, reason }` with `reason` one of `"elimination"`,
+    /// `"immobilization"`, `"dux_captured"`, `"dux_trapped"`,
+    /// `"resignation"` or `"timeout"`, or `{ kind: "draw", reason }` with
+    /// `reason` one of `"repetition"`, `"move_limit"` or `"agreement"`.
+    #[wasm_bindgen(getter)]
+    pub fn result(&self) -> JsValue {
+        match self.game.result() {
+            Some(result) => serde_wasm_bindgen::to_value(&result).unwrap(),
+            None => JsValue::NULL,
         }
     }
-    
+
+    /// The result as a sentence, e.g. `"Light wins by elimination"`, or
+    /// `undefined` while the game goes on.
+    #[wasm_bindgen(getter)]
+    pub fn result_text(&self) -> Option<String> {
+        self.game.result().map(|result| result.to_string())
+    }
+
+    /// Moves without a capture after which the game is drawn, or
+    /// `undefined` for no limit.
+    #[wasm_bindgen(getter)]
+    pub fn move_limit(&self) -> Option<u16> {
+        self.game.rules().move_limit
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn phase(&self) -> String {
+        self.game.phase().as_str().to_string()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn selected_square(&self) -> Option<usize> {
-        self.selected_square
+        self.selected_path.first().copied()
     }
-    
+
+    /// The selected piece followed by the squares picked so far along its
+    /// jump chain; empty with nothing selected.
+    #[wasm_bindgen(getter)]
+    pub fn selected_path(&self) -> Vec<usize> {
+        self.selected_path.clone()
+    }
+
     pub fn get_board(&self) -> JsValue {
-        let board_array: Vec<u8> = self.board.iter().map(|sq| {
+        let board_array: Vec<u8> = self.game.position().board().squares().iter().map(|sq| {
             match sq {
                 Square::Empty => 0,
-                Square::Piece(Player::Light) => 1,
-                Square::Piece(Player::Dark) => 2,
+                Square::Piece(Player::Light, PieceKind::Soldier) => 1,
+                Square::Piece(Player::Dark, PieceKind::Soldier) => 2,
+                Square::Piece(Player::Light, PieceKind::Dux) => 3,
+                Square::Piece(Player::Dark, PieceKind::Dux) => 4,
             }
         }).collect();
         serde_wasm_bindgen::to_value(&board_array).unwrap()
     }
-    
+
     pub fn get_valid_moves(&self) -> JsValue {
-        let mut moves = Vec::new();
-        
-        match self.phase {
-            GamePhase::Placement => {
-                // In placement phase, all empty squares are valid
-                for i in 0..64 {
-                    if matches!(self.board[i], Square::Empty) {
-                        moves.push(i);
-                    }
-                }
-            }
-            GamePhase::Movement => {
-                if let Some(selected) = self.selected_square {
-                    // Verify the selected square still has a piece (it might have been captured)
-                    if matches!(self.board[selected], Square::Piece(p) if p == self.current_player) {
-                        // Get valid destinations from selected square
-                        // Use must_continue_jumping flag to filter moves if we're in a jump sequence
-                        moves = self.get_valid_destinations(selected);
-                    } else {
-                        // Selected square is empty or has wrong piece - clear selection
-                        // This can happen if the piece was captured
-                        // Don't clear here, let select_square handle it
-                        moves = Vec::new();
-                    }
-                } else {
-                    // Get all squares that can be selected (player's pieces)
-                    // When checking if a piece can be selected, always check ALL moves (not filtered by must_continue_jumping)
-                    for i in 0..64 {
-                        if let Square::Piece(p) = self.board[i] {
-                            if p == self.current_player {
-                                // Check if this piece has any valid moves (check all moves, not just jumps)
-                                // Use jumps_only=false to see all possible moves when selecting a new piece
-                                if !self.get_valid_destinations_internal(i, false).is_empty() {
-                                    moves.push(i);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        serde_wasm_bindgen::to_value(&self.valid_squares()).unwrap()
+    }
+
+    /// Every legal move for the side to move, e.g. `{ Step: [9, 10] }` or
+    /// `{ MultiJump: [0, 2, 18] }`.
+    pub fn get_legal_moves(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.game.legal_moves()).unwrap()
+    }
+
+    /// Plays a move in the form returned by `get_legal_moves`, made at
+    /// `now_ms` if a clock is set. Returns false if the value is not a move,
+    /// the move is not legal, the clock is running and `now_ms` is missing,
+    /// or the mover's flag has fallen.
+    pub fn play_move(&mut self, mv: JsValue, now_ms: Option<f64>) -> bool {
+        let Ok(mv) = serde_wasm_bindgen::from_value::<Move>(mv) else {
+            return false;
+        };
+        let now_ms = now_ms.map(|ms| ms as u64);
+        if self.check_clock(now_ms).is_err() || self.apply(mv, now_ms).is_err() {
+            return false;
         }
-        
-        serde_wasm_bindgen::to_value(&moves).unwrap()
-    }
-    
-    fn get_valid_destinations(&self, from: usize) -> Vec<usize> {
-        self.get_valid_destinations_internal(from, self.must_continue_jumping)
-    }
-    
-    fn get_valid_destinations_jumps_only(&self, from: usize) -> Vec<usize> {
-        self.get_valid_destinations_internal(from, true)
-    }
-    
-    fn get_valid_destinations_internal(&self, from: usize, jumps_only: bool) -> Vec<usize> {
-        let mut destinations = Vec::new();
-        
-        if from >= 64 {
-            return destinations;
+        self.selected_path.clear();
+        true
+    }
+
+    /// Handles a click on `square`: places a piece, selects a piece, or
+    /// moves the selected piece. Returns `{ ok: true, action }` with
+    /// `action` one of `"placed"`, `"selected"`, `"hopped"` or `"moved"`, or
+    /// `{ ok: false, code, message }` saying why nothing happened, e.g.
+    /// `code: "not_your_piece"`. `now_ms` is the time of the click, needed
+    /// while the clock is running.
+    pub fn select_square(&mut self, square: usize, now_ms: Option<f64>) -> JsValue {
+        let response = select::SelectResponse::from(self.select_at(square, now_ms.map(|ms| ms as u64)));
+        serde_wasm_bindgen::to_value(&response).unwrap()
+    }
+
+    /// Searches for the best move for the side to move. `depth_or_time` is
+    /// either a number of plies, e.g. `4`, or a time budget such as
+    /// `{ time_ms: 500 }`. Returns the move in the form used by
+    /// `get_legal_moves`, or `null` once the game is over.
+    pub fn best_move(&self, depth_or_time: JsValue) -> Result<JsValue, JsValue> {
+        let limit: SearchLimit = serde_wasm_bindgen::from_value(depth_or_time)
+            .map_err(|_| JsValue::from_str("expected a depth or { time_ms }"))?;
+        if self.game.game_over() {
+            return Ok(JsValue::NULL);
         }
-        
-        let row = from / 8;
-        let col = from % 8;
-        
-        // Check orthogonal directions (up, down, left, right)
-        let directions = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-        
-        for (dr, dc) in directions.iter() {
-            let new_row = row as i32 + dr;
-            let new_col = col as i32 + dc;
-            
-            if new_row >= 0 && new_row < 8 && new_col >= 0 && new_col < 8 {
-                let to = (new_row * 8 + new_col) as usize;
-                
-                match self.board[to] {
-                    Square::Empty => {
-                        if !jumps_only {
-                            destinations.push(to);
-                        }
-                    }
-                    Square::Piece(p) => {
-                        // Check if we can jump over this piece
-                        if p != self.current_player {
-                            let jump_row = new_row + dr;
-                            let jump_col = new_col + dc;
-                            
-                            if jump_row >= 0 && jump_row < 8 && jump_col >= 0 && jump_col < 8 {
-                                let jump_to = (jump_row * 8 + jump_col) as usize;
-                                if matches!(self.board[jump_to], Square::Empty) {
-                                    destinations.push(jump_to);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        let result = Searcher::with_weights(self.eval_weights).search(self.game.position(), limit);
+        Ok(serde_wasm_bindgen::to_value(&result.best_move).unwrap())
+    }
+
+    /// Pieces of the side to move that the opponent could capture if it
+    /// were their turn, each as `{ square, moves }` with every enemy move
+    /// that captures it in the form used by `get_legal_moves`.
+    pub fn threats(&self) -> JsValue {
+        if self.game.game_over() {
+            return serde_wasm_bindgen::to_value(&Vec::<Threat>::new()).unwrap();
         }
-        
-        destinations
+        serde_wasm_bindgen::to_value(&self.game.position().threats()).unwrap()
     }
-    
-    pub fn select_square(&mut self, square: usize) -> bool {
-        if square >= 64 || self.game_over {
-            return false;
+
+    /// Suggests a move for the side to move, searching for up to `time_ms`
+    /// milliseconds at full strength. Returns `{ mv, reason, message }`:
+    /// `mv` in the form used by `get_legal_moves`, `reason` such as
+    /// `{ kind: "captures", count: 2 }`, `{ kind: "escapes_threat" }`,
+    /// `{ kind: "sets_up_capture" }` or `{ kind: "positional" }`, and
+    /// `message` a phrase to show the player. Returns `null` once the game
+    /// is over.
+    pub fn suggest_move(&self, time_ms: u32) -> JsValue {
+        if self.game.game_over() {
+            return JsValue::NULL;
         }
-        
-        match self.phase {
-            GamePhase::Placement => {
-                // In placement phase, clicking an empty square places a piece
-                if matches!(self.board[square], Square::Empty) {
-                    self.board[square] = Square::Piece(self.current_player);
-                    
-                    match self.current_player {
-                        Player::Light => {
-                            self.light_pieces_placed += 1;
-                            if self.light_pieces_placed == 8 {
-                                // Check if all pieces are placed
-                                if self.dark_pieces_placed == 8 {
-                                    self.phase = GamePhase::Movement;
-                                } else {
-                                    self.switch_player();
-                                }
-                            } else {
-                                self.switch_player();
-                            }
-                        }
-                        Player::Dark => {
-                            self.dark_pieces_placed += 1;
-                            if self.dark_pieces_placed == 8 {
-                                // All pieces placed, switch to movement phase
-                                self.phase = GamePhase::Movement;
-                            } else {
-                                self.switch_player();
-                            }
-                        }
-                    }
-                    
-                    self.check_captures();
-                    self.check_win_condition();
-                    return true;
-                }
-            }
-            GamePhase::Movement => {
-                if let Some(selected) = self.selected_square {
-                    // Verify the selected square still has a piece (it might have been captured)
-                    if !matches!(self.board[selected], Square::Piece(p) if p == self.current_player) {
-                        // Selected square is empty or has wrong piece - clear selection
-                        self.selected_square = None;
-                        self.must_continue_jumping = false;
-                        // Fall through to try selecting the clicked square
-                    } else {
-                        // Check if user clicked on another piece of the same player (reselect)
-                        if let Square::Piece(p) = self.board[square] {
-                            if p == self.current_player && square != selected {
-                                // User clicked on a different piece of the same player - reselect it
-                                // When selecting a NEW piece, check ALL moves (not filtered by must_continue_jumping)
-                                let valid_destinations = self.get_valid_destinations_internal(square, false);
-                                if !valid_destinations.is_empty() {
-                                    self.selected_square = Some(square);
-                                    self.must_continue_jumping = false; // Reset when selecting a new piece
-                                    self.piece_that_captured = None; // Reset capture tracking when selecting new piece
-                                    return true;
-                                }
-                            }
-                        }
-                        
-                        // If we must continue jumping, only allow jump moves
-                        if self.must_continue_jumping {
-                            let row_from = selected / 8;
-                            let col_from = selected % 8;
-                            let row_to = square / 8;
-                            let col_to = square % 8;
-                            let dr = (row_to as i32) - (row_from as i32);
-                            let dc = (col_to as i32) - (col_from as i32);
-                            let is_jump = dr.abs() == 2 || dc.abs() == 2;
-                            
-                            if !is_jump {
-                                // Trying to make a regular move when jumps are required
-                                // But allow reselecting if clicking on another piece
-                                if let Square::Piece(p) = self.board[square] {
-                                    if p == self.current_player && square != selected {
-                                        // User clicked on a different piece - allow reselection
-                                        let valid_destinations = self.get_valid_destinations_internal(square, false);
-                                        if !valid_destinations.is_empty() {
-                                            self.selected_square = Some(square);
-                                            self.must_continue_jumping = false;
-                                            self.piece_that_captured = None;
-                                            return true;
-                                        }
-                                    }
-                                }
-                                return false;
-                            }
-                        }
-                        
-                        // Try to move to this square
-                        if self.make_move(selected, square) {
-                            // Check if the piece still exists at the destination (it might have been captured)
-                            let piece_still_exists = matches!(self.board[square], Square::Piece(p) if p == self.current_player);
-                            
-                            // Check if this was a jump and if more jumps are possible
-                            let row_from = selected / 8;
-                            let col_from = selected % 8;
-                            let row_to = square / 8;
-                            let col_to = square % 8;
-                            let dr = (row_to as i32) - (row_from as i32);
-                            let dc = (col_to as i32) - (col_from as i32);
-                            
-                            let was_jump = dr.abs() == 2 || dc.abs() == 2;
-                            
-                        if was_jump && piece_still_exists {
-                            // Check if more jumps are available from the new position
-                            // Only check for jump moves (distance == 2)
-                            // But only if this piece hasn't already captured
-                            if self.piece_that_captured == Some(square) {
-                                // This piece has already captured, no more moves allowed
-                                self.selected_square = None;
-                                self.must_continue_jumping = false;
-                                self.piece_that_captured = None;
-                                self.switch_player();
-                            } else {
-                                let additional_jumps = self.get_valid_destinations_jumps_only(square);
-                                
-                                if !additional_jumps.is_empty() {
-                                    // More jumps available - keep piece selected and only allow jump moves
-                                    self.selected_square = Some(square);
-                                    self.must_continue_jumping = true;
-                                } else {
-                                    // No more jumps, deselect and switch player
-                                    self.selected_square = None;
-                                    self.must_continue_jumping = false;
-                                    self.piece_that_captured = None;
-                                    self.switch_player();
-                                }
-                            }
-                        } else {
-                            // Regular move, or piece was captured - deselect and switch player
-                            self.selected_square = None;
-                            self.must_continue_jumping = false;
-                            self.piece_that_captured = None;
-                            self.switch_player();
-                        }
-                            return true;
-                        } else {
-                            // Invalid move - keep piece selected so user can try a different destination
-                            // Don't deselect here, only deselect when user selects a different piece
-                            return false;
-                        }
-                    }
-                }
-                
-                // No piece selected, or selected piece was invalid - try to select the clicked square
-                // Select a piece
-                if let Square::Piece(p) = self.board[square] {
-                    if p == self.current_player {
-                        // When selecting a NEW piece, check ALL moves (not filtered by must_continue_jumping)
-                        // The must_continue_jumping flag should only apply when a piece is already selected
-                        let valid_destinations = self.get_valid_destinations_internal(square, false);
-                        if !valid_destinations.is_empty() {
-                            self.selected_square = Some(square);
-                            self.must_continue_jumping = false; // Reset when selecting a new piece
-                            return true;
-                        }
-                    }
-                }
-            }
+        let hint = suggest_move(self.game.position(), SearchLimit::Time { time_ms }, self.eval_weights);
+        serde_wasm_bindgen::to_value(&hint).unwrap()
+    }
+
+    /// Static score of the position for `player`; positive favours them.
+    /// Uses the weights set by `set_eval_weights`.
+    pub fn evaluate(&self, player: Player) -> i32 {
+        evaluate(self, player)
+    }
+
+    /// Replaces the evaluation weights, e.g.
+    /// `{ material: 100, mobility: 2, threatened: 30, edge: 4, corner: 10,
+    /// jump_opportunities: 20 }`. Missing fields keep their default.
+    pub fn set_eval_weights(&mut self, weights: JsValue) -> Result<(), JsValue> {
+        self.eval_weights = serde_wasm_bindgen::from_value(weights)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(())
+    }
+
+    /// Sets the strength of `ai_move`: `"novice"`, `"beginner"`,
+    /// `"intermediate"`, `"advanced"` or `"expert"`. Throws for any other
+    /// name.
+    pub fn set_ai_level(&mut self, level: &str) -> Result<(), JsValue> {
+        self.ai_level = level.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ai_level(&self) -> String {
+        self.ai_level.as_str().to_string()
+    }
+
+    /// A move for the side to move chosen at the current AI level, mistakes
+    /// included. Returns `null` once the game is over.
+    pub fn ai_move(&self) -> JsValue {
+        if self.game.game_over() {
+            return JsValue::NULL;
         }
-        
-        false
+        let seed = (js_sys::Math::random() * u32::MAX as f64) as u64;
+        let mv = AiPlayer::new(self.ai_level, seed)
+            .with_weights(self.eval_weights)
+            .choose_move(self.game.position());
+        serde_wasm_bindgen::to_value(&mv).unwrap()
     }
-    
-    fn make_move(&mut self, from: usize, to: usize) -> bool {
-        if from >= 64 || to >= 64 {
+
+    /// Searches for the best move by Monte Carlo tree search with
+    /// `playouts` random playouts, at least one. The same `seed` always
+    /// gives the same move. Returns `null` once the game is over.
+    pub fn best_move_mcts(&self, playouts: u32, seed: u32) -> JsValue {
+        if self.game.game_over() {
+            return JsValue::NULL;
+        }
+        let config = MctsConfig {
+            playouts,
+            seed: u64::from(seed),
+            ..MctsConfig::default()
+        };
+        let result = MctsSearcher::new(config).search(self.game.position());
+        serde_wasm_bindgen::to_value(&result.best_move).unwrap()
+    }
+
+    /// Squares of the pieces captured by the last move, for animating their
+    /// removal. Empty if nothing was captured or no move has been played.
+    pub fn last_captured(&self) -> Vec<usize> {
+        self.game.last_move().map(|played| played.captured.clone()).unwrap_or_default()
+    }
+
+    /// Ends the game with a win for `player`'s opponent. Throws if the game
+    /// is already over.
+    pub fn resign(&mut self, player: Player) -> Result<(), JsValue> {
+        self.game.resign(player).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.stop_clock();
+        self.emit_game_over();
+        Ok(())
+    }
+
+    /// Offers `player`'s opponent a draw, to be answered with `accept_draw`
+    /// or `decline_draw`; moving instead declines it. Throws if the game is
+    /// over or a draw is already on offer.
+    pub fn offer_draw(&mut self, player: Player) -> Result<(), JsValue> {
+        self.game.offer_draw(player).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.emit(GameEvent::DrawOffered { player });
+        Ok(())
+    }
+
+    /// Accepts the draw on offer. Throws if there is none.
+    pub fn accept_draw(&mut self) -> Result<(), JsValue> {
+        self.game.accept_draw().map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.stop_clock();
+        self.emit_game_over();
+        Ok(())
+    }
+
+    /// Declines the draw on offer. Throws if there is none.
+    pub fn decline_draw(&mut self) -> Result<(), JsValue> {
+        let offerer = self.game.draw_offer();
+        self.game.decline_draw().map_err(|e| JsValue::from_str(&e.to_string()))?;
+        if let Some(offerer) = offerer {
+            self.emit(GameEvent::DrawDeclined {
+                player: offerer.opponent(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The player whose draw offer is waiting for an answer, or
+    /// `undefined`.
+    #[wasm_bindgen(getter)]
+    pub fn draw_offer(&self) -> Option<Player> {
+        self.game.draw_offer()
+    }
+
+    /// Events since the last call, oldest first, e.g.
+    /// `{ type: "piece_jumped", player: "Light", from: 9, over: 10, to: 11,
+    /// captured: true }`. Events passed to an event callback are not kept.
+    pub fn drain_events(&mut self) -> JsValue {
+        serde_wasm_bindgen::to_value(&self.take_events()).unwrap()
+    }
+
+    /// Calls `callback` with each event as it happens instead of keeping it
+    /// for `drain_events`. Pass `null` or `undefined` to go back to
+    /// draining.
+    ///
+    /// The callback runs in the middle of the move, so it must not call
+    /// back into the game: wasm-bindgen would throw "recursive use of an
+    /// object". Defer such work, e.g. with `queueMicrotask`. An exception
+    /// thrown by the callback is logged to the console and does not undo
+    /// the move.
+    pub fn set_event_callback(&mut self, callback: Option<js_sys::Function>) {
+        self.event_callback = callback;
+    }
+
+    /// Time controls for JavaScript, e.g. `{ kind: "sudden_death", base_ms:
+    /// 300000 }`, `{ kind: "fischer", base_ms, increment_ms }`, `{ kind:
+    /// "bronstein", base_ms, delay_ms }` or `{ kind: "byo_yomi", base_ms,
+    /// period_ms, periods }`. Sets a fresh, stopped clock; `null` removes
+    /// it. Throws if the value is not a time control.
+    pub fn set_time_control(&mut self, control: JsValue) -> Result<(), JsValue> {
+        let control: Option<TimeControl> = serde_wasm_bindgen::from_value(control)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.set_clock(control.map(Clock::new));
+        Ok(())
+    }
+
+    /// Starts the side to move's time at `now_ms`. The clock also starts
+    /// with the first timed move. Throws if there is no clock or the game
+    /// is over.
+    pub fn start_clock(&mut self, now_ms: f64) -> Result<(), JsValue> {
+        if self.game.game_over() {
+            return Err(JsValue::from_str("the game is over"));
+        }
+        let player = self.game.current_player();
+        let clock = self.clock.as_mut().ok_or_else(|| JsValue::from_str("no time control is set"))?;
+        clock.start(player, now_ms as u64);
+        Ok(())
+    }
+
+    /// Both players' time at `now_ms`, as `{ light, dark, running }` with
+    /// each side `{ remaining_ms, main_ms, periods }`, or `null` without a
+    /// clock.
+    pub fn clock_state(&self, now_ms: f64) -> JsValue {
+        match &self.clock {
+            Some(clock) => serde_wasm_bindgen::to_value(&clock.state(now_ms as u64)).unwrap(),
+            None => JsValue::NULL,
+        }
+    }
+
+    /// Ends the game on time if the running player's flag has fallen by
+    /// `now_ms`. Returns true if it did; call it regularly while the clock
+    /// runs.
+    pub fn check_time(&mut self, now_ms: f64) -> bool {
+        self.check_clock(Some(now_ms as u64)) == Err(SelectError::GameOver)
+    }
+
+    /// Takes back the last move. Returns false if there is nothing to undo
+    /// or the game is timed, since the clock cannot be taken back with it.
+    pub fn undo(&mut self) -> bool {
+        if !self.can_undo() {
             return false;
         }
-        
-        // Check if move is valid - use jumps_only=false to check ALL moves
-        // The must_continue_jumping flag is only for UI filtering, not move validation
-        let valid_destinations = self.get_valid_destinations_internal(from, false);
-        if !valid_destinations.contains(&to) {
+        self.selected_path.clear();
+        self.game.undo()
+    }
+
+    /// Replays the last undone move. Returns false if there is nothing to
+    /// redo or the game is timed.
+    pub fn redo(&mut self) -> bool {
+        if !self.can_redo() {
             return false;
         }
-        
-        // Check if this is a jump
-        let row_from = from / 8;
-        let col_from = from % 8;
-        let row_to = to / 8;
-        let col_to = to % 8;
-        
-        let dr = (row_to as i32) - (row_from as i32);
-        let dc = (col_to as i32) - (col_from as i32);
-        
-        // Check if this piece has already captured this turn
-        if let Some(captured_piece_pos) = self.piece_that_captured {
-            if captured_piece_pos == from {
-                // This piece has already captured, don't allow another move
-                return false;
+        self.selected_path.clear();
+        self.game.redo()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_undo(&self) -> bool {
+        self.clock.is_none() && self.game.can_undo()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn can_redo(&self) -> bool {
+        self.clock.is_none() && self.game.can_redo()
+    }
+
+    /// Moves played so far, oldest first, each as
+    /// `{ player, mv, captured }`.
+    pub fn history(&self) -> JsValue {
+        serde_wasm_bindgen::to_value(self.game.history()).unwrap()
+    }
+
+    /// Starts a new game under the same rules and time control.
+    pub fn reset(&mut self) {
+        self.game = Game::with_rules(*self.game.rules());
+        self.selected_path.clear();
+        self.events.clear();
+        self.reset_clock();
+    }
+
+    pub fn set_starting_pieces(&mut self) {
+        self.game = Game::starting_with_rules(*self.game.rules());
+        self.selected_path.clear();
+        self.events.clear();
+        self.reset_clock();
+    }
+}
+
+impl GameState {
+    /// Wraps `game` with nothing selected and the default AI level.
+    pub fn from_game(game: Game) -> GameState {
+        GameState {
+            game,
+            selected_path: Vec::new(),
+            ai_level: AiLevel::default(),
+            eval_weights: EvalWeights::default(),
+            events: Vec::new(),
+            event_callback: None,
+            clock: None,
+        }
+    }
+
+    /// The game behind this state, for native callers.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn eval_weights(&self) -> &EvalWeights {
+        &self.eval_weights
+    }
+
+    pub fn eval_weights_mut(&mut self) -> &mut EvalWeights {
+        &mut self.eval_weights
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// Replaces the clock; `None` plays untimed.
+    pub fn set_clock(&mut self, clock: Option<Clock>) {
+        self.clock = clock;
+    }
+
+    /// Handles a click on `square`; see `select_square`.
+    pub fn select(&mut self, square: usize) -> Result<Selection, SelectError> {
+        self.select_at(square, None)
+    }
+
+    /// Handles a click on `square` made at `now_ms`. The mover's time is
+    /// charged when a click completes their move.
+    pub fn select_at(&mut self, square: usize, now_ms: Option<u64>) -> Result<Selection, SelectError> {
+        if self.game.game_over() {
+            return Err(SelectError::GameOver);
+        }
+        self.check_clock(now_ms)?;
+        let board = self.game.position().board();
+        if square >= board.len() {
+            return Err(SelectError::OffBoard);
+        }
+        let owner = board.get(square).owner();
+
+        if self.game.phase() == GamePhase::Placement {
+            if owner.is_some() {
+                return Err(SelectError::Occupied);
             }
+            self.apply(Move::Place(square), now_ms).map_err(|_| SelectError::Occupied)?;
+            return Ok(Selection::Placed);
         }
-        
-        // Check if we jumped over an enemy piece
-        let mut captured_by_jump = false;
-        if dr.abs() == 2 || dc.abs() == 2 {
-            // This is a jump
-            let jumped_row = row_from + (dr / 2) as usize;
-            let jumped_col = col_from + (dc / 2) as usize;
-            let jumped_index = jumped_row * 8 + jumped_col;
-            
-            // Check if there's an enemy piece to jump over
-            if let Square::Piece(jumped_piece) = self.board[jumped_index] {
-                if jumped_piece != self.current_player {
-                    // Remove the jumped piece (capture by jump)
-                    self.board[jumped_index] = Square::Empty;
-                    captured_by_jump = true;
+
+        let moves = self.game.legal_moves();
+        if !self.selected_path.is_empty() {
+            // A jump chain is chosen by clicking the square it ends on. Where
+            // several chains end there, or a chain could stop there or go
+            // on, the squares it lands on are clicked in turn instead, and
+            // clicking the last of them again stops there. A move going
+            // straight to the square wins over longer chains ending there
+            let chains = self.chains(&moves);
+            let hop = self.selected_path.len();
+            let ending: Vec<&Move> = chains.iter().copied().filter(|mv| mv.to() == square).collect();
+            let going_on = chains.iter().any(|mv| mv.path().len() > hop + 1 && mv.path()[hop] == square);
+            if going_on {
+                self.selected_path.push(square);
+                return Ok(Selection::Hopped);
+            }
+            let chosen = match ending[..] {
+                [mv] => Some(mv),
+                [_, _, ..] => {
+                    let direct = ending.iter().find(|mv| mv.path().len() == hop + 1);
+                    Some(*direct.ok_or(SelectError::AmbiguousMove)?)
                 }
+                [] => None,
+            };
+            if let Some(mv) = chosen {
+                self.apply(mv.clone(), now_ms).map_err(|_| SelectError::NotADestination)?;
+                self.selected_path.clear();
+                return Ok(Selection::Moved);
+            }
+            if square == self.selected_path[0] {
+                return Err(SelectError::AlreadySelected);
             }
         }
-        
-        // Move the piece
-        let piece = self.board[from];
-        self.board[from] = Square::Empty;
-        self.board[to] = piece;
-        
-        // If this piece captured by jump, mark it and return early
-        // Don't check for surrounding captures if it already captured by jump
-        if captured_by_jump {
-            self.piece_that_captured = Some(to);
-            self.check_win_condition();
-            return true;
+
+        // Select a piece, or reselect a different one. An invalid
+        // destination keeps the current piece selected.
+        match owner {
+            None if !self.selected_path.is_empty() => Err(SelectError::NotADestination),
+            None => Err(SelectError::EmptySquare),
+            Some(player) if player != self.game.current_player() => Err(SelectError::NotYourPiece),
+            Some(_) if !moves.iter().any(|mv| mv.from() == Some(square)) => Err(SelectError::PieceCannotMove),
+            Some(_) => {
+                self.selected_path = vec![square];
+                Ok(Selection::Selected)
+            }
         }
-        
-        // Check for captures after move (surrounding captures)
-        // Note: check_captures might remove the piece we just moved if it gets captured
-        let capturing_pieces = self.check_captures();
-        
-        // If this piece (at 'to') caused a capture by surrounding, mark it
-        if capturing_pieces.contains(&to) && self.piece_that_captured.is_none() {
-            self.piece_that_captured = Some(to);
+    }
+
+    /// The moves in `moves` that start along the selected path.
+    fn chains<'a>(&self, moves: &'a [Move]) -> Vec<&'a Move> {
+        moves
+            .iter()
+            .filter(|mv| mv.path().starts_with(&self.selected_path))
+            .collect()
+    }
+
+    /// Events since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Plays `mv`, made at `now_ms`, and records what happened. The clock
+    /// must have been checked first.
+    fn apply(&mut self, mv: Move, now_ms: Option<u64>) -> Result<Vec<usize>, MoveError> {
+        let before = self.game.position().clone();
+        let offer = self.game.draw_offer();
+        let captured = self.game.apply(mv.clone())?;
+        // The whole turn, jump chain included, ends with the move
+        let next = self.game.current_player();
+        if let (Some(clock), Some(now_ms)) = (&mut self.clock, now_ms) {
+            clock.press(next, now_ms).expect("the clock was checked before the move");
         }
-        
-        // If the piece we just moved was captured, we need to handle that
-        // This can happen if moving to a square causes the piece to be surrounded
-        if matches!(self.board[to], Square::Empty) {
-            // The piece was captured immediately after moving
-            // This is valid - the move succeeded, but the piece was captured
-            // The selected_square will be handled in select_square
+        if self.game.game_over() {
+            self.stop_clock();
         }
-        
-        // Note: Player switching is handled in select_square based on whether more jumps are available
-        // This allows the UI to keep the piece selected for additional jumps
-        
-        self.check_win_condition();
-        true
-    }
-    
-    fn check_captures(&mut self) -> Vec<usize> {
-        // Check all squares for captures
-        // Returns list of positions of pieces that caused captures (for the current player)
-        let mut captured_squares = Vec::new();
-        let mut capturing_pieces = Vec::new(); // Track which pieces caused captures
-        
-        for i in 0..64 {
-            if let Square::Piece(piece_player) = self.board[i] {
-                // Check if this piece is surrounded on opposite sides
-                let row = i / 8;
-                let col = i % 8;
-                
-                // Check horizontal (left-right)
-                let left = if col > 0 { Some(i - 1) } else { None };
-                let right = if col < 7 { Some(i + 1) } else { None };
-                
-                if let (Some(left_idx), Some(right_idx)) = (left, right) {
-                    if let (Square::Piece(left_p), Square::Piece(right_p)) = 
-                        (self.board[left_idx], self.board[right_idx]) {
-                        if left_p != piece_player && right_p != piece_player && left_p == right_p {
-                            // Captured horizontally
-                            captured_squares.push(i);
-                            // Track which pieces caused this capture (only current player's pieces)
-                            if left_p == self.current_player {
-                                capturing_pieces.push(left_idx);
-                            }
-                            if right_p == self.current_player {
-                                capturing_pieces.push(right_idx);
-                            }
-                            continue;
-                        }
-                    }
-                }
-                
-                // Check vertical (up-down)
-                let up = if row > 0 { Some(i - 8) } else { None };
-                let down = if row < 7 { Some(i + 8) } else { None };
-                
-                if let (Some(up_idx), Some(down_idx)) = (up, down) {
-                    if let (Square::Piece(up_p), Square::Piece(down_p)) = 
-                        (self.board[up_idx], self.board[down_idx]) {
-                        if up_p != piece_player && down_p != piece_player && up_p == down_p {
-                            // Captured vertically
-                            captured_squares.push(i);
-                            // Track which pieces caused this capture (only current player's pieces)
-                            if up_p == self.current_player {
-                                capturing_pieces.push(up_idx);
-                            }
-                            if down_p == self.current_player {
-                                capturing_pieces.push(down_idx);
-                            }
-                        }
-                    }
-                }
-            }
+        if let (Some(offerer), None) = (offer, self.game.draw_offer()) {
+            self.emit(GameEvent::DrawDeclined {
+                player: offerer.opponent(),
+            });
         }
-        
-        // Apply captures after checking all squares to avoid double-capturing
-        for square in captured_squares {
-            self.board[square] = Square::Empty;
+        for event in events::move_events(&before, &mv, &captured, &self.game) {
+            self.emit(event);
         }
-        
-        // Return list of pieces that caused captures
-        capturing_pieces
+        Ok(captured)
     }
-    
-    fn switch_player(&mut self) {
-        self.current_player = match self.current_player {
-            Player::Light => Player::Dark,
-            Player::Dark => Player::Light,
+
+    /// Fails if the clock is running and `now_ms` is missing, or if the
+    /// running player's flag has fallen, which ends the game.
+    fn check_clock(&mut self, now_ms: Option<u64>) -> Result<(), SelectError> {
+        let Some(clock) = self.clock.as_ref().filter(|clock| clock.running().is_some()) else {
+            return Ok(());
         };
-        // Reset capture tracking when switching players
-        self.piece_that_captured = None;
-    }
-    
-    fn check_win_condition(&mut self) {
-        if self.phase != GamePhase::Movement {
-            return;
+        let now_ms = now_ms.ok_or(SelectError::TimestampRequired)?;
+        if let Some(player) = clock.flagged(now_ms) {
+            self.stop_clock();
+            if self.game.time_out(player).is_ok() {
+                self.emit_game_over();
+            }
+            return Err(SelectError::GameOver);
         }
-        
-        // Count pieces
-        let light_count = self.board.iter()
-            .filter(|s| matches!(s, Square::Piece(Player::Light)))
-            .count();
-        let dark_count = self.board.iter()
-            .filter(|s| matches!(s, Square::Piece(Player::Dark)))
-            .count();
-        
-        // Check if a player has no pieces - the player with pieces left wins
-        if light_count == 0 && dark_count > 0 {
-            // No Light pieces left, Dark pieces remain - Dark wins
-            self.game_over = true;
-            self.winner = Some(Player::Dark);
-            return;
+        Ok(())
+    }
+
+    fn stop_clock(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
         }
-        if dark_count == 0 && light_count > 0 {
-            // No Dark pieces left, Light pieces remain - Light wins
-            self.game_over = true;
-            self.winner = Some(Player::Light);
-            return;
+    }
+
+    fn reset_clock(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            *clock = Clock::new(clock.control());
         }
-        
-        // Check if current player has any valid moves
-        let has_valid_moves = (0..64).any(|i| {
-            if let Square::Piece(p) = self.board[i] {
-                if p == self.current_player {
-                    return !self.get_valid_destinations(i).is_empty();
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        match &self.event_callback {
+            Some(callback) => {
+                let event = serde_wasm_bindgen::to_value(&event).unwrap();
+                // An exception in the callback must not undo the move
+                if let Err(error) = callback.call1(&JsValue::NULL, &event) {
+                    console_error("event callback failed:", &error);
                 }
             }
-            false
-        });
-        
-        if !has_valid_moves {
-            self.game_over = true;
-            // The player who cannot move loses
-            self.winner = Some(match self.current_player {
-                Player::Light => Player::Dark,
-                Player::Dark => Player::Light,
-            });
+            None => self.events.push(event),
         }
     }
-    
-    pub fn reset(&mut self) {
-        *self = GameState::new();
-    }
-    
-    pub fn set_starting_pieces(&mut self) {
-        // Clear the board first
-        self.board = [Square::Empty; 64];
-        self.light_pieces_placed = 0;
-        self.dark_pieces_placed = 0;
-        self.phase = GamePhase::Placement;
-        self.current_player = Player::Light;
-        self.selected_square = None;
-        self.must_continue_jumping = false;
-        self.piece_that_captured = None;
-        self.game_over = false;
-        self.winner = None;
-        
-        // Place light pieces on H1-H8 (column 7, rows 0-7)
-        for row in 0..8 {
-            let index = row * 8 + 7; // Column 7 (H)
-            self.board[index] = Square::Piece(Player::Light);
-            self.light_pieces_placed += 1;
-        }
-        
-        // Place dark pieces on A1-A8 (column 0, rows 0-7)
-        for row in 0..8 {
-            let index = row * 8 + 0; // Column 0 (A)
-            self.board[index] = Square::Piece(Player::Dark);
-            self.dark_pieces_placed += 1;
+
+    fn emit_game_over(&mut self) {
+        if let Some(result) = self.game.result() {
+            self.selected_path.clear();
+            self.emit(GameEvent::GameOver { result });
         }
-        
-        // All pieces are placed, switch to movement phase
-        self.phase = GamePhase::Movement;
-        self.current_player = Player::Light;
-        
-        // Check for any initial captures
-        self.check_captures();
-        self.check_win_condition();
+    }
+
+    /// Squares to highlight: placeable squares, the destinations of the
+    /// selected piece and the next squares its jump chains land on, or the
+    /// pieces that can be selected.
+    fn valid_squares(&self) -> Vec<usize> {
+        let moves = self.game.legal_moves();
+        let hop = self.selected_path.len();
+        let mut squares: Vec<usize> = match self.game.phase() {
+            GamePhase::Placement => moves.iter().map(Move::to).collect(),
+            GamePhase::Movement if hop > 0 => self.chains(&moves)
+                .into_iter()
+                .flat_map(|mv| [Some(mv.to()), mv.path().get(hop).copied()])
+                .flatten()
+                .collect(),
+            GamePhase::Movement => moves.iter().filter_map(Move::from).collect(),
+        };
+        squares.sort_unstable();
+        squares.dedup();
+        squares
     }
 }
 
+/// Scores the game in `state` for `player` with the state's evaluation
+/// weights; positive favours `player`.
+pub fn evaluate(state: &GameState, player: Player) -> i32 {
+    evaluate_position(state.game.position(), player, &state.eval_weights)
+}
+
 #[wasm_bindgen]
 pub fn init() {
     console_error_panic_hook::set_once();
 }
-
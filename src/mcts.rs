@@ -0,0 +1,209 @@
+//! Computer opponent: Monte Carlo tree search.
+//!
+//! Rather than searching every move to a fixed depth, MCTS samples the game
+//! with random playouts and grows its tree towards the moves that win most
+//! often, which copes better with the wide placement phase than
+//! [alpha-beta](crate::Searcher). Playouts use the ordinary move generation,
+//! so jump chains are single moves here too.
+
+use serde::Serialize;
+
+use crate::game::winner;
+use crate::moves::Move;
+use crate::position::Position;
+use crate::rng::Rng;
+use crate::Player;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    /// Number of playouts per search. At least one is always run.
+    pub playouts: u32,
+    /// Seed for the random choices. The same seed, position and config
+    /// always give the same move.
+    pub seed: u64,
+    /// Weight of the exploration term in the UCT formula.
+    pub exploration: f64,
+    /// Plies after which an unfinished playout counts as a draw.
+    pub max_playout_plies: u32,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig {
+            playouts: 2000,
+            seed: 0,
+            exploration: std::f64::consts::SQRT_2,
+            max_playout_plies: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MctsResult {
+    /// The most visited move, or `None` if the game is already over.
+    pub best_move: Option<Move>,
+    /// Playouts that went through the best move.
+    pub visits: u32,
+    /// Share of those playouts the side to move won, counting draws as half.
+    pub win_rate: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    // Move leading here from the parent, played by `player`
+    mv: Option<Move>,
+    player: Player,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Move>,
+    visits: u32,
+    // Playouts won by `player`, draws counting half
+    wins: f64,
+}
+
+/// Searches positions by Monte Carlo tree search.
+#[derive(Debug, Clone)]
+pub struct MctsSearcher {
+    config: MctsConfig,
+    rng: Rng,
+}
+
+impl MctsSearcher {
+    pub fn new(config: MctsConfig) -> MctsSearcher {
+        MctsSearcher {
+            config,
+            rng: Rng::new(config.seed),
+        }
+    }
+
+    pub fn config(&self) -> &MctsConfig {
+        &self.config
+    }
+
+    /// Finds the best move for the side to move in `position`. Each search
+    /// starts again from the configured seed.
+    pub fn search(&mut self, position: &Position) -> MctsResult {
+        self.rng = Rng::new(self.config.seed);
+
+        let root_moves = if winner(position).is_some() {
+            Vec::new()
+        } else {
+            position.legal_moves()
+        };
+        if root_moves.is_empty() {
+            return MctsResult {
+                best_move: None,
+                visits: 0,
+                win_rate: 0.0,
+            };
+        }
+
+        let mut nodes = vec![Node {
+            mv: None,
+            // The root's player is whoever moved last; it is never scored
+            player: position.current_player().opponent(),
+            parent: None,
+            children: Vec::new(),
+            untried: root_moves,
+            visits: 0,
+            wins: 0.0,
+        }];
+
+        // One playout expands a root child to return
+        for _ in 0..self.config.playouts.max(1) {
+            let mut current = position.clone();
+            let mut node = 0;
+
+            // Selection: descend through fully expanded nodes
+            while nodes[node].untried.is_empty() && !nodes[node].children.is_empty() {
+                node = self.select_child(&nodes, node);
+                current.play(nodes[node].mv.clone().expect("only the root has no move"));
+            }
+
+            // Expansion: add one untried move
+            if !nodes[node].untried.is_empty() {
+                let index = self.rng.below(nodes[node].untried.len());
+                let mv = nodes[node].untried.swap_remove(index);
+                let player = current.current_player();
+                current.play(mv.clone());
+                let untried = if winner(&current).is_some() {
+                    Vec::new()
+                } else {
+                    current.legal_moves()
+                };
+                nodes.push(Node {
+                    mv: Some(mv),
+                    player,
+                    parent: Some(node),
+                    children: Vec::new(),
+                    untried,
+                    visits: 0,
+                    wins: 0.0,
+                });
+                let child = nodes.len() - 1;
+                nodes[node].children.push(child);
+                node = child;
+            }
+
+            // Simulation, then backpropagation of the result
+            let result = self.playout(current);
+            let mut next = Some(node);
+            while let Some(index) = next {
+                let node = &mut nodes[index];
+                node.visits += 1;
+                node.wins += match result {
+                    Some(player) if player == node.player => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+                next = node.parent;
+            }
+        }
+
+        let best = nodes[0]
+            .children
+            .iter()
+            .copied()
+            .max_by_key(|&child| nodes[child].visits)
+            .expect("the root has at least one move");
+        let best = &nodes[best];
+        MctsResult {
+            best_move: best.mv.clone(),
+            visits: best.visits,
+            win_rate: if best.visits == 0 { 0.0 } else { best.wins / f64::from(best.visits) },
+        }
+    }
+
+    /// The child of `parent` with the highest UCT score.
+    fn select_child(&self, nodes: &[Node], parent: usize) -> usize {
+        let log_visits = f64::from(nodes[parent].visits.max(1)).ln();
+        let uct = |child: usize| {
+            let node = &nodes[child];
+            let visits = f64::from(node.visits.max(1));
+            node.wins / visits + self.config.exploration * (log_visits / visits).sqrt()
+        };
+        nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .max_by(|&a, &b| uct(a).total_cmp(&uct(b)))
+            .expect("selection only visits expanded nodes")
+    }
+
+    /// Plays uniformly random moves until the game ends, returning the
+    /// winner, or `None` if the ply limit is reached first.
+    fn playout(&mut self, mut position: Position) -> Option<Player> {
+        for _ in 0..self.config.max_playout_plies {
+            if let Some(winner) = winner(&position) {
+                return Some(winner);
+            }
+            let moves = position.legal_moves();
+            if moves.is_empty() {
+                return None;
+            }
+            let mv = moves[self.rng.below(moves.len())].clone();
+            position.play(mv);
+        }
+        winner(&position)
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A complete move for the side to move. Squares are board indices,
+/// `row * width + col`, with A1 at index 0.
+///
+/// A jump chain is a single move: [`Move::Jump`] for one hop and
+/// [`Move::MultiJump`] for two or more.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Move {
+    /// Place a new piece on an empty square (placement phase).
+    Place(usize),
+    /// Move orthogonally to an empty square: one square, or any distance
+    /// under [rook movement](crate::Movement::Rook).
+    Step(usize, usize),
+    /// Jump from the first square over the enemy piece on the second square,
+    /// landing on the third.
+    Jump(usize, usize, usize),
+    /// A chain of jumps by one piece. The path lists every square the piece
+    /// stands on, starting with its origin.
+    MultiJump(Vec<usize>),
+}
+
+impl Move {
+    /// Builds a jump or jump chain from the squares the piece lands on.
+    pub(crate) fn from_jump_path(path: Vec<usize>) -> Move {
+        if path.len() == 2 {
+            Move::Jump(path[0], (path[0] + path[1]) / 2, path[1])
+        } else {
+            Move::MultiJump(path)
+        }
+    }
+
+    /// The square the moving piece starts on, or `None` for a placement.
+    pub fn from(&self) -> Option<usize> {
+        match self {
+            Move::Place(_) => None,
+            Move::Step(from, _) | Move::Jump(from, _, _) => Some(*from),
+            Move::MultiJump(path) => path.first().copied(),
+        }
+    }
+
+    /// The square the piece ends up on.
+    pub fn to(&self) -> usize {
+        match self {
+            Move::Place(to) | Move::Step(_, to) | Move::Jump(_, _, to) => *to,
+            Move::MultiJump(path) => path.last().copied().unwrap_or_default(),
+        }
+    }
+
+    /// Every square the piece stands on during the move, origin first.
+    pub fn path(&self) -> Vec<usize> {
+        match self {
+            Move::Place(to) => vec![*to],
+            Move::Step(from, to) | Move::Jump(from, _, to) => vec![*from, *to],
+            Move::MultiJump(path) => path.clone(),
+        }
+    }
+
+    /// The squares of the enemy pieces jumped over, in order.
+    pub fn jumped(&self) -> Vec<usize> {
+        match self {
+            Move::Place(_) | Move::Step(_, _) => Vec::new(),
+            Move::Jump(_, over, _) => vec![*over],
+            Move::MultiJump(path) => path.windows(2).map(|hop| (hop[0] + hop[1]) / 2).collect(),
+        }
+    }
+
+    pub fn is_jump(&self) -> bool {
+        matches!(self, Move::Jump(..) | Move::MultiJump(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    /// The game has already finished.
+    GameOver,
+    /// The move is not legal in the current position.
+    IllegalMove,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::GameOver => write!(f, "the game is over"),
+            MoveError::IllegalMove => write!(f, "illegal move"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
@@ -0,0 +1,338 @@
+//! Compact text notation for positions, in the spirit of chess FEN.
+//!
+//! A position is written as five space-separated fields:
+//!
+//! ```text
+//! D6L/D6L/D6L/D6L/D6L/D6L/D6L/D6L l m 8 8
+//! ```
+//!
+//! 1. The board, top rank first and rank 1 last, ranks separated by `/`.
+//!    Each rank lists its files from A: `L` is a Light piece, `D` a Dark
+//!    piece and a number is a run of empty squares. A piece followed by `+`
+//!    is that side's dux. The number of ranks and files must match the
+//!    board size of the rules; [`Position::from_notation`] takes the board
+//!    size from them.
+//! 2. The side to move: `l` or `d`.
+//! 3. The phase: `p` for placement or `m` for movement.
+//! 4. The number of pieces Light has placed.
+//! 5. The number of pieces Dark has placed.
+//!
+//! Jump chains are played as single moves, so a position is never in the
+//! middle of one and needs no pending-jump field.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::{Board, PieceKind, Square, MAX_BOARD_SIZE};
+use crate::position::{GamePhase, Position};
+use crate::rules::RuleSet;
+use crate::Player;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationError {
+    /// The string does not have exactly five fields.
+    FieldCount(usize),
+    /// The board does not have as many ranks as the rules' board.
+    RankCount { expected: usize, found: usize },
+    /// A rank (numbered from 1) does not describe one square per file.
+    RankLength { rank: usize, expected: usize, found: usize },
+    /// A board character is neither a piece nor a digit.
+    InvalidSquare(char),
+    InvalidSideToMove(String),
+    InvalidPhase(String),
+    /// A placed-piece counter is not a number up to the pieces per side.
+    InvalidCounter { counter: String, max: u8 },
+    /// A player has more pieces on the board than they have placed.
+    TooManyPieces(Player),
+    /// A player has a dux the rules do not allow, or more than one.
+    InvalidDux(Player),
+    /// The rules are invalid, or the phase, counters and side to move do not
+    /// describe a reachable turn.
+    Inconsistent(&'static str),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotationError::FieldCount(n) => write!(f, "expected 5 fields, found {}", n),
+            NotationError::RankCount { expected, found } => {
+                write!(f, "expected {} ranks, found {}", expected, found)
+            }
+            NotationError::RankLength { rank, expected, found } => write!(
+                f,
+                "rank {} describes {} squares instead of {}",
+                rank, found, expected
+            ),
+            NotationError::InvalidSquare(c) => write!(f, "invalid board character '{}'", c),
+            NotationError::InvalidSideToMove(s) => {
+                write!(f, "invalid side to move '{}', expected 'l' or 'd'", s)
+            }
+            NotationError::InvalidPhase(s) => write!(f, "invalid phase '{}', expected 'p' or 'm'", s),
+            NotationError::InvalidCounter { counter, max } => write!(
+                f,
+                "invalid placed-piece counter '{}', expected 0-{}",
+                counter, max
+            ),
+            NotationError::TooManyPieces(player) => {
+                write!(f, "{:?} has more pieces on the board than placed", player)
+            }
+            NotationError::InvalidDux(player) => write!(f, "{:?} has an invalid dux", player),
+            NotationError::Inconsistent(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+impl Position {
+    /// Writes the position in the notation described in this module.
+    pub fn to_notation(&self) -> String {
+        let board = self.board();
+        let mut ranks = Vec::with_capacity(board.height());
+        for row in (0..board.height()).rev() {
+            let mut rank = String::new();
+            let mut empty = 0;
+            for col in 0..board.width() {
+                let (player, kind) = match board.get(board.index(row, col)) {
+                    Square::Empty => {
+                        empty += 1;
+                        continue;
+                    }
+                    Square::Piece(player, kind) => (player, kind),
+                };
+                if empty > 0 {
+                    rank.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                rank.push(match player {
+                    Player::Light => 'L',
+                    Player::Dark => 'D',
+                });
+                if kind == PieceKind::Dux {
+                    rank.push('+');
+                }
+            }
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+            }
+            ranks.push(rank);
+        }
+
+        let side = match self.current_player() {
+            Player::Light => 'l',
+            Player::Dark => 'd',
+        };
+        let phase = match self.phase() {
+            GamePhase::Placement => 'p',
+            GamePhase::Movement => 'm',
+        };
+
+        format!(
+            "{} {} {} {} {}",
+            ranks.join("/"),
+            side,
+            phase,
+            self.pieces_placed(Player::Light),
+            self.pieces_placed(Player::Dark)
+        )
+    }
+
+    /// Reads a position written by [`Position::to_notation`] under the
+    /// default rules on a board of the size the notation describes,
+    /// rejecting positions that could not arise in a game.
+    pub fn from_notation(notation: &str) -> Result<Position, NotationError> {
+        Position::from_notation_with_rules(notation, sized_rules(notation, RuleSet::default()))
+    }
+
+    /// Reads a position written by [`Position::to_notation`] to be played
+    /// under `rules`.
+    pub fn from_notation_with_rules(notation: &str, rules: RuleSet) -> Result<Position, NotationError> {
+        rules.validate().map_err(NotationError::Inconsistent)?;
+
+        let fields: Vec<&str> = notation.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(NotationError::FieldCount(fields.len()));
+        }
+
+        let board = parse_board(fields[0], rules.width, rules.height)?;
+
+        let current_player = match fields[1] {
+            "l" => Player::Light,
+            "d" => Player::Dark,
+            other => return Err(NotationError::InvalidSideToMove(other.to_string())),
+        };
+        let phase = match fields[2] {
+            "p" => GamePhase::Placement,
+            "m" => GamePhase::Movement,
+            other => return Err(NotationError::InvalidPhase(other.to_string())),
+        };
+        let light_pieces_placed = parse_counter(fields[3], rules.pieces_per_side)?;
+        let dark_pieces_placed = parse_counter(fields[4], rules.pieces_per_side)?;
+
+        let position = Position::from_parts(
+            rules,
+            board,
+            current_player,
+            phase,
+            light_pieces_placed,
+            dark_pieces_placed,
+        );
+        validate(&position)?;
+        Ok(position)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_notation())
+    }
+}
+
+impl FromStr for Position {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Position::from_notation(s)
+    }
+}
+
+/// `rules` on a board of the size described by `notation`, taken from its
+/// number of ranks and the length of its top rank. The rules are returned
+/// unchanged if the board field is too malformed to tell, leaving the error
+/// to the parser.
+pub(crate) fn sized_rules(notation: &str, rules: RuleSet) -> RuleSet {
+    let Some(field) = notation.split_whitespace().next() else {
+        return rules;
+    };
+    let height = field.split('/').count();
+    let top = field.split('/').next().unwrap_or_default();
+    let mut width = 0;
+    let mut empty = 0;
+    for c in top.chars() {
+        match c.to_digit(10) {
+            Some(digit) => empty = empty * 10 + digit as usize,
+            None => {
+                width += empty + usize::from(c != '+');
+                empty = 0;
+            }
+        }
+        // Stop before a long run of digits can overflow
+        if width + empty > MAX_BOARD_SIZE {
+            return rules;
+        }
+    }
+    width += empty;
+    let sized = rules.with_board_size(width, height);
+    if sized.validate().is_ok() { sized } else { rules }
+}
+
+fn parse_board(field: &str, width: usize, height: usize) -> Result<Board, NotationError> {
+    let ranks: Vec<&str> = field.split('/').collect();
+    if ranks.len() != height {
+        return Err(NotationError::RankCount {
+            expected: height,
+            found: ranks.len(),
+        });
+    }
+
+    let mut board = Board::new(width, height);
+    for (i, rank) in ranks.iter().enumerate() {
+        let row = height - 1 - i;
+        let wrong_length = |found| NotationError::RankLength {
+            rank: row + 1,
+            expected: width,
+            found,
+        };
+        let mut squares = Vec::with_capacity(width);
+        let mut empty = 0;
+        for c in rank.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                empty = empty * 10 + digit as usize;
+                if squares.len() + empty > width {
+                    return Err(wrong_length(squares.len() + empty));
+                }
+                continue;
+            }
+            squares.extend(std::iter::repeat_n(Square::Empty, empty));
+            empty = 0;
+            let square = match c {
+                'L' => Square::Piece(Player::Light, PieceKind::Soldier),
+                'D' => Square::Piece(Player::Dark, PieceKind::Soldier),
+                // Promotes the piece just read to a dux
+                '+' => match squares.pop() {
+                    Some(Square::Piece(player, PieceKind::Soldier)) => Square::Piece(player, PieceKind::Dux),
+                    _ => return Err(NotationError::InvalidSquare('+')),
+                },
+                other => return Err(NotationError::InvalidSquare(other)),
+            };
+            squares.push(square);
+        }
+        squares.extend(std::iter::repeat_n(Square::Empty, empty));
+
+        if squares.len() != width {
+            return Err(wrong_length(squares.len()));
+        }
+        for (col, square) in squares.into_iter().enumerate() {
+            board.set(board.index(row, col), square);
+        }
+    }
+
+    Ok(board)
+}
+
+fn parse_counter(field: &str, max: u8) -> Result<u8, NotationError> {
+    match field.parse::<u8>() {
+        Ok(n) if n <= max => Ok(n),
+        _ => Err(NotationError::InvalidCounter {
+            counter: field.to_string(),
+            max,
+        }),
+    }
+}
+
+fn validate(position: &Position) -> Result<(), NotationError> {
+    for player in [Player::Light, Player::Dark] {
+        if position.piece_count(player) > position.pieces_placed(player) as usize {
+            return Err(NotationError::TooManyPieces(player));
+        }
+        let duxes = position
+            .board()
+            .squares()
+            .iter()
+            .filter(|s| s.is_dux() && s.owner() == Some(player))
+            .count();
+        if duxes > 1 || (duxes == 1 && !position.rules().dux) {
+            return Err(NotationError::InvalidDux(player));
+        }
+    }
+
+    let light = position.pieces_placed(Player::Light);
+    let dark = position.pieces_placed(Player::Dark);
+    let pieces = position.rules().pieces_per_side;
+    let all_placed = light == pieces && dark == pieces;
+
+    match position.phase() {
+        GamePhase::Movement if !all_placed => Err(NotationError::Inconsistent(
+            "movement phase before all pieces are placed",
+        )),
+        GamePhase::Placement if all_placed => Err(NotationError::Inconsistent(
+            "placement phase after all pieces are placed",
+        )),
+        GamePhase::Placement => {
+            // Light places first and the players alternate
+            let to_place = if light == dark { Player::Light } else { Player::Dark };
+            if light != dark && light != dark + 1 {
+                Err(NotationError::Inconsistent(
+                    "placement counters differ by more than one",
+                ))
+            } else if position.current_player() != to_place {
+                Err(NotationError::Inconsistent(
+                    "side to move does not match the placement counters",
+                ))
+            } else {
+                Ok(())
+            }
+        }
+        GamePhase::Movement => Ok(()),
+    }
+}
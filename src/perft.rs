@@ -0,0 +1,56 @@
+//! Perft: counting every move sequence of a given length, to check move
+//! generation against known numbers.
+//!
+//! A jump chain is one move, as everywhere else. Sequences end early at
+//! positions where the game is over, which have no moves to count.
+
+use crate::game::winner;
+use crate::moves::Move;
+use crate::position::Position;
+
+impl Position {
+    /// Number of move sequences `depth` moves long from this position.
+    /// Depth 0 counts the position itself.
+    pub fn perft(&self, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        if winner(self).is_some() {
+            return 0;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .into_iter()
+            .map(|mv| {
+                let mut child = self.clone();
+                child.play(mv);
+                child.perft(depth - 1)
+            })
+            .sum()
+    }
+
+    /// [`Position::perft`] broken down by first move: each legal move with
+    /// the number of sequences `depth` moves long that start with it, in
+    /// move generation order. The counts add up to `perft(depth)`.
+    ///
+    /// # Panics
+    ///
+    /// If `depth` is 0, which has no first move to break the count down by.
+    pub fn divide(&self, depth: u8) -> Vec<(Move, u64)> {
+        assert!(depth > 0, "divide needs a depth of at least 1");
+        if winner(self).is_some() {
+            return Vec::new();
+        }
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let mut child = self.clone();
+                child.play(mv.clone());
+                (mv, child.perft(depth - 1))
+            })
+            .collect()
+    }
+}
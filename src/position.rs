@@ -0,0 +1,575 @@
+use serde::Serialize;
+
+use crate::bitboard::Bitboard;
+use crate::board::{Board, PieceKind, Square};
+use crate::moves::{Move, MoveError};
+use crate::rules::{CustodialCapture, Movement, MultiJumps, RuleSet};
+use crate::zobrist;
+use crate::Player;
+
+/// The four orthogonal directions as `(ranks, files)`: down, up, left and
+/// right.
+pub(crate) const DIRECTIONS: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// For each of [`DIRECTIONS`], the squares from which a piece of one player
+/// could step, or jump over an enemy soldier, in that direction.
+struct MoveSets {
+    /// Squares whose neighbour that way is empty, whatever stands on them,
+    /// so that rook slides can follow them square by square.
+    steps: [Bitboard; 4],
+    jumps: [Bitboard; 4],
+}
+
+impl MoveSets {
+    fn new(board: &Board, player: Player) -> MoveSets {
+        let empty = board.empty_squares();
+        let jumpable = board.pieces(player.opponent()) & !board.duxes();
+        let steps = DIRECTIONS.map(|(dr, dc)| board.shift(empty, -dr, -dc));
+        let mut jumps = [Bitboard::EMPTY; 4];
+        for (i, (dr, dc)) in DIRECTIONS.into_iter().enumerate() {
+            jumps[i] = board.shift(jumpable & steps[i], -dr, -dc);
+        }
+        MoveSets { steps, jumps }
+    }
+
+    /// Squares with at least one step or jump.
+    fn movable(&self) -> Bitboard {
+        self.steps
+            .into_iter()
+            .chain(self.jumps)
+            .fold(Bitboard::EMPTY, |movable, squares| movable | squares)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GamePhase {
+    Placement,
+    Movement,
+}
+
+impl GamePhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GamePhase::Placement => "placement",
+            GamePhase::Movement => "movement",
+        }
+    }
+}
+
+/// A board position together with the side to move, the placement counters
+/// and the rules it is played under.
+///
+/// `Position` knows the rules of movement and capture but nothing about how a
+/// game ends; see [`Game`](crate::Game) for that.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    rules: RuleSet,
+    board: Board,
+    current_player: Player,
+    phase: GamePhase,
+    light_pieces_placed: u8,
+    dark_pieces_placed: u8,
+    // Zobrist hash, kept up to date by every change above
+    hash: u64,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position::new()
+    }
+}
+
+impl Position {
+    /// An empty board at the start of the placement phase, Light to place,
+    /// under the default rules.
+    pub fn new() -> Position {
+        Position::with_rules(RuleSet::default())
+    }
+
+    /// An empty board at the start of the placement phase under `rules`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rules` fails [`RuleSet::validate`].
+    pub fn with_rules(rules: RuleSet) -> Position {
+        if let Err(reason) = rules.validate() {
+            panic!("invalid rules: {}", reason);
+        }
+        Position::from_parts(
+            rules,
+            Board::new(rules.width, rules.height),
+            Player::Light,
+            GamePhase::Placement,
+            0,
+            0,
+        )
+    }
+
+    pub(crate) fn from_parts(
+        rules: RuleSet,
+        board: Board,
+        current_player: Player,
+        phase: GamePhase,
+        light_pieces_placed: u8,
+        dark_pieces_placed: u8,
+    ) -> Position {
+        let mut position = Position {
+            rules,
+            board,
+            current_player,
+            phase,
+            light_pieces_placed,
+            dark_pieces_placed,
+            hash: 0,
+        };
+        position.hash = zobrist::hash(&position);
+        position
+    }
+
+    /// The standard opening setup under the default rules: Light on the H
+    /// file, Dark on the A file, movement phase with Light to move.
+    pub fn starting() -> Position {
+        Position::starting_with_rules(RuleSet::default())
+    }
+
+    /// The opening setup under `rules`. Dark fills files from the left edge
+    /// inwards, rank 1 first, and Light the mirror-image squares from the
+    /// right edge, until all pieces are placed.
+    pub fn starting_with_rules(rules: RuleSet) -> Position {
+        let mut position = Position::with_rules(rules);
+        let pieces = rules.pieces_per_side as usize;
+
+        // Light's squares mirror Dark's through the centre of the board, so
+        // the two sides never overlap
+        for i in 0..pieces {
+            let dark = position.board.index(i % rules.height, i / rules.height);
+            let light = position.board.len() - 1 - dark;
+            let kind = if i == 0 && rules.dux { PieceKind::Dux } else { PieceKind::Soldier };
+            position.board.set(light, Square::Piece(Player::Light, kind));
+            position.board.set(dark, Square::Piece(Player::Dark, kind));
+        }
+
+        position.light_pieces_placed = rules.pieces_per_side;
+        position.dark_pieces_placed = rules.pieces_per_side;
+        position.phase = GamePhase::Movement;
+        position.hash = zobrist::hash(&position);
+
+        // Check for any initial captures
+        if rules.custodial == CustodialCapture::AllFlanked {
+            position.capture_all_flanked();
+        }
+        position
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    pub fn current_player(&self) -> Player {
+        self.current_player
+    }
+
+    pub fn phase(&self) -> GamePhase {
+        self.phase
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn square(&self, index: usize) -> Square {
+        self.board.get(index)
+    }
+
+    /// Zobrist hash of the board, side to move, phase and placement
+    /// counters. Equal positions under the same rules have equal hashes,
+    /// which makes it suitable for detecting repetitions.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn pieces_placed(&self, player: Player) -> u8 {
+        match player {
+            Player::Light => self.light_pieces_placed,
+            Player::Dark => self.dark_pieces_placed,
+        }
+    }
+
+    /// The square of `player`'s dux, if it is on the board.
+    pub fn dux_square(&self, player: Player) -> Option<usize> {
+        (self.board.pieces(player) & self.board.duxes()).lowest()
+    }
+
+    /// Whether `player` has lost their dux: the rules give each side one,
+    /// `player` has placed it, and it is no longer on the board.
+    pub fn dux_captured(&self, player: Player) -> bool {
+        self.rules.dux && self.pieces_placed(player) > 0 && self.dux_square(player).is_none()
+    }
+
+    /// Whether `player`'s dux is on the board but has no step or jump.
+    pub fn dux_trapped(&self, player: Player) -> bool {
+        self.dux_square(player)
+            .is_some_and(|dux| !self.movable_pieces(player).contains(dux))
+    }
+
+    /// Number of `player`'s pieces currently on the board.
+    pub fn piece_count(&self, player: Player) -> usize {
+        self.board.pieces(player).count()
+    }
+
+    /// Number of first steps and jumps `player`'s pieces have, whoever is to
+    /// move.
+    pub fn mobility(&self, player: Player) -> usize {
+        let board = &self.board;
+        let pieces = board.pieces(player);
+        let empty = board.empty_squares();
+        let jumpable = board.pieces(player.opponent()) & !board.duxes();
+        let mut moves = 0;
+        for (dr, dc) in DIRECTIONS {
+            // Jumps: an enemy soldier next to the piece and an empty square
+            // beyond
+            let jump_landings = board.shift(board.shift(pieces, dr, dc) & jumpable, dr, dc) & empty;
+            moves += jump_landings.count();
+
+            // Steps, following each piece's ray square by square when
+            // pieces slide like rooks
+            let mut reached = board.shift(pieces, dr, dc) & empty;
+            while !reached.is_empty() {
+                moves += reached.count();
+                if self.rules.movement == Movement::Step {
+                    break;
+                }
+                reached = board.shift(reached, dr, dc) & empty;
+            }
+        }
+        moves
+    }
+
+    /// Squares the piece on `from` can reach with its first step or jump.
+    ///
+    /// Returns an empty list if `from` does not hold a piece of the side to move.
+    pub fn destinations(&self, from: usize) -> Vec<usize> {
+        if from >= self.board.len() || self.board.get(from).owner() != Some(self.current_player) {
+            return Vec::new();
+        }
+        let sets = MoveSets::new(&self.board, self.current_player);
+        let mut moves = Vec::new();
+        self.piece_moves(&sets, from, false, &mut moves);
+        moves.iter().map(Move::to).collect()
+    }
+
+    /// Records the steps (or slides) and single jumps for the piece on
+    /// `from`, with `sets` computed for its owner.
+    fn piece_moves(&self, sets: &MoveSets, from: usize, jumps_only: bool, moves: &mut Vec<Move>) {
+        for (i, (dr, dc)) in DIRECTIONS.into_iter().enumerate() {
+            if sets.jumps[i].contains(from) {
+                let over = self.board.step(from, dr, dc);
+                moves.push(Move::Jump(from, over, self.board.step(over, dr, dc)));
+            } else if !jumps_only && sets.steps[i].contains(from) {
+                let mut to = self.board.step(from, dr, dc);
+                moves.push(Move::Step(from, to));
+
+                // Rook-like pieces keep sliding until the next square is
+                // occupied or off the board
+                if self.rules.movement == Movement::Rook {
+                    while sets.steps[i].contains(to) {
+                        to = self.board.step(to, dr, dc);
+                        moves.push(Move::Step(from, to));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Every move the side to move can play in this position.
+    ///
+    /// Jump chains are listed as whole moves. Where the rules make further
+    /// jumps mandatory, only chains that cannot be extended are listed.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+
+        match self.phase {
+            GamePhase::Placement => {
+                // In placement phase, all empty squares are valid
+                moves.extend(self.board.empty_squares().squares().map(Move::Place));
+            }
+            GamePhase::Movement => {
+                let sets = MoveSets::new(&self.board, self.current_player);
+                for from in (sets.movable() & self.board.pieces(self.current_player)).squares() {
+                    self.collect_piece_moves(&sets, from, &mut moves);
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Records every move of the piece on `from`, jump chains included, with
+    /// `sets` computed for the side to move.
+    fn collect_piece_moves(&self, sets: &MoveSets, from: usize, moves: &mut Vec<Move>) {
+        let start = moves.len();
+        self.piece_moves(sets, from, false, moves);
+        // Steps stay as they are; jumps are replaced by the chains they
+        // start, in the same order
+        if !moves[start..].iter().any(Move::is_jump) {
+            return;
+        }
+        for mv in moves.split_off(start) {
+            match mv {
+                Move::Jump(from, _, to) => {
+                    let mut after = self.clone();
+                    after.jump(from, to);
+                    after.collect_jump_chains(&mut vec![from, to], moves);
+                }
+                step => moves.push(step),
+            }
+        }
+    }
+
+    /// Extends the jump chain in `path`, played out on `self`, and records
+    /// every chain that has to stop.
+    fn collect_jump_chains(&self, path: &mut Vec<usize>, moves: &mut Vec<Move>) {
+        let at = path[path.len() - 1];
+
+        // A chain never jumps over the same piece twice
+        let mut continuations = Vec::new();
+        if self.rules.multi_jumps != MultiJumps::Never {
+            let jumped = Move::MultiJump(path.clone()).jumped();
+            let mut jumps = Vec::new();
+            self.piece_moves(&MoveSets::new(&self.board, self.current_player), at, true, &mut jumps);
+            continuations = jumps.iter().map(Move::to).collect();
+            continuations.retain(|to| !jumped.contains(&((at + to) / 2)));
+        }
+
+        if continuations.is_empty() || self.rules.multi_jumps == MultiJumps::Optional {
+            moves.push(Move::from_jump_path(path.clone()));
+        }
+
+        for to in continuations {
+            let mut after = self.clone();
+            after.jump(at, to);
+            path.push(to);
+            after.collect_jump_chains(path, moves);
+            path.pop();
+        }
+    }
+
+    pub fn is_legal(&self, mv: &Move) -> bool {
+        match (self.phase, mv) {
+            (GamePhase::Placement, Move::Place(square)) => {
+                *square < self.board.len() && self.board.get(*square) == Square::Empty
+            }
+            (GamePhase::Movement, Move::Step(..) | Move::Jump(..) | Move::MultiJump(_)) => {
+                // Only the moving piece's own moves need generating
+                let Some(from) = mv.from().filter(|&from| from < self.board.len()) else {
+                    return false;
+                };
+                if self.board.get(from).owner() != Some(self.current_player) {
+                    return false;
+                }
+                let mut moves = Vec::new();
+                self.collect_piece_moves(&MoveSets::new(&self.board, self.current_player), from, &mut moves);
+                moves.contains(mv)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the side to move has at least one move available.
+    pub fn has_legal_moves(&self) -> bool {
+        let board = &self.board;
+        match self.phase {
+            GamePhase::Placement => !board.empty_squares().is_empty(),
+            GamePhase::Movement => {
+                // Stops at the first direction any piece can step or jump in
+                let pieces = board.pieces(self.current_player);
+                let empty = board.empty_squares();
+                let jumpable = board.pieces(self.current_player.opponent()) & !board.duxes();
+                DIRECTIONS.into_iter().any(|(dr, dc)| {
+                    let can_step = board.shift(empty, -dr, -dc);
+                    !(pieces & can_step).is_empty()
+                        || !(pieces & board.shift(jumpable & can_step, -dr, -dc)).is_empty()
+                })
+            }
+        }
+    }
+
+    /// `player`'s pieces that have at least one step or jump.
+    fn movable_pieces(&self, player: Player) -> Bitboard {
+        MoveSets::new(&self.board, player).movable() & self.board.pieces(player)
+    }
+
+    /// Plays `mv` for the side to move, removing any captured pieces and
+    /// passing the turn. Returns the squares of the pieces that were captured.
+    pub fn apply(&mut self, mv: Move) -> Result<Vec<usize>, MoveError> {
+        if !self.is_legal(&mv) {
+            return Err(MoveError::IllegalMove);
+        }
+        Ok(self.play(mv))
+    }
+
+    /// Plays a move taken from [`Position::legal_moves`] without checking
+    /// it again.
+    pub(crate) fn play(&mut self, mv: Move) -> Vec<usize> {
+        match mv {
+            Move::Place(square) => self.place(square),
+            Move::Step(from, to) => {
+                self.move_piece(from, to);
+                // Check for captures after move (surrounding captures)
+                let captured = self.check_captures(to);
+                self.switch_player();
+                captured
+            }
+            Move::Jump(..) | Move::MultiJump(_) => {
+                let mut captured = Vec::new();
+                for hop in mv.path().windows(2) {
+                    if self.jump(hop[0], hop[1]) {
+                        captured.push((hop[0] + hop[1]) / 2);
+                    }
+                }
+                // A piece that has captured by jumping ends the turn without
+                // surrounding captures
+                if captured.is_empty() {
+                    captured = self.check_captures(mv.to());
+                }
+                self.switch_player();
+                captured
+            }
+        }
+    }
+
+    /// Plays a single jump without passing the turn and reports whether the
+    /// jumped piece was captured.
+    fn jump(&mut self, from: usize, to: usize) -> bool {
+        self.move_piece(from, to);
+        if self.rules.jumps_capture {
+            self.set_square((from + to) / 2, Square::Empty);
+        }
+        self.rules.jumps_capture
+    }
+
+    fn place(&mut self, square: usize) -> Vec<usize> {
+        // With a dux in play, each player's first piece is their dux
+        let kind = if self.rules.dux && self.pieces_placed(self.current_player) == 0 {
+            PieceKind::Dux
+        } else {
+            PieceKind::Soldier
+        };
+        self.set_square(square, Square::Piece(self.current_player, kind));
+
+        let placed = self.pieces_placed(self.current_player);
+        self.hash ^= zobrist::placed_key(self.current_player, placed)
+            ^ zobrist::placed_key(self.current_player, placed + 1);
+        match self.current_player {
+            Player::Light => self.light_pieces_placed += 1,
+            Player::Dark => self.dark_pieces_placed += 1,
+        }
+
+        if self.light_pieces_placed == self.rules.pieces_per_side
+            && self.dark_pieces_placed == self.rules.pieces_per_side
+        {
+            // All pieces placed, switch to movement phase. The last player to
+            // place also makes the first move.
+            self.phase = GamePhase::Movement;
+            self.hash ^= zobrist::phase_key(GamePhase::Movement);
+        } else {
+            self.switch_player();
+        }
+
+        self.check_captures(square)
+    }
+
+    fn move_piece(&mut self, from: usize, to: usize) {
+        let piece = self.board.get(from);
+        self.set_square(from, Square::Empty);
+        self.set_square(to, piece);
+    }
+
+    /// Removes the pieces captured by custodianship now that a piece has
+    /// arrived on `square`, returning their squares.
+    fn check_captures(&mut self, square: usize) -> Vec<usize> {
+        match self.rules.custodial {
+            CustodialCapture::Disabled => Vec::new(),
+            CustodialCapture::ByMover => self.capture_by_mover(square),
+            CustodialCapture::AllFlanked => self.capture_all_flanked(),
+        }
+    }
+
+    fn capture_by_mover(&mut self, square: usize) -> Vec<usize> {
+        let board = &self.board;
+        let Some(mover) = board.get(square).owner() else {
+            return Vec::new();
+        };
+        let mover_pieces = board.pieces(mover);
+        let enemies = board.pieces(mover.opponent());
+        let beside_mover = |index: usize, (dr, dc): (i32, i32)| {
+            board.offset(index, dr, dc).is_some_and(|beyond| mover_pieces.contains(beyond))
+        };
+
+        let mut captured = Bitboard::EMPTY;
+        for direction in DIRECTIONS {
+            let Some(neighbour) = board.offset(square, direction.0, direction.1).filter(|&i| enemies.contains(i)) else {
+                continue;
+            };
+            // An enemy soldier next to the piece that arrived is captured if
+            // one of the mover's pieces stands directly beyond it, and a dux
+            // if the mover's pieces enclose it on all four sides
+            let taken = if board.duxes().contains(neighbour) {
+                DIRECTIONS.into_iter().all(|side| beside_mover(neighbour, side))
+            } else {
+                beside_mover(neighbour, direction)
+            };
+            if taken {
+                captured.insert(neighbour);
+            }
+        }
+        if captured.is_empty() {
+            return Vec::new();
+        }
+
+        let captured_squares: Vec<usize> = captured.squares().collect();
+        for &square in &captured_squares {
+            self.set_square(square, Square::Empty);
+        }
+
+        captured_squares
+    }
+
+    fn capture_all_flanked(&mut self) -> Vec<usize> {
+        let board = &self.board;
+        let mut captured = Bitboard::EMPTY;
+        for player in [Player::Light, Player::Dark] {
+            // Squares with an enemy on the side in each direction
+            let enemies = board.pieces(player.opponent());
+            let [below, above, left, right] = DIRECTIONS.map(|(dr, dc)| board.shift(enemies, -dr, -dc));
+
+            // Soldiers surrounded on opposite sides, horizontally or
+            // vertically, and duxes enclosed on all four sides
+            let pieces = board.pieces(player);
+            let soldiers = pieces & !board.duxes();
+            captured = captured
+                | (soldiers & ((left & right) | (below & above)))
+                | (pieces & board.duxes() & left & right & below & above);
+        }
+
+        // Apply captures after checking all squares to avoid double-capturing
+        let captured_squares: Vec<usize> = captured.squares().collect();
+        for &square in &captured_squares {
+            self.set_square(square, Square::Empty);
+        }
+
+        captured_squares
+    }
+
+    pub(crate) fn switch_player(&mut self) {
+        self.hash ^= zobrist::side_key(Player::Dark);
+        self.current_player = self.current_player.opponent();
+    }
+
+    /// Changes one square of the board, keeping the hash up to date.
+    fn set_square(&mut self, index: usize, square: Square) {
+        self.hash ^= zobrist::square_key(index, self.board.get(index)) ^ zobrist::square_key(index, square);
+        self.board.set(index, square);
+    }
+}
@@ -0,0 +1,555 @@
+//! Game records in a PGN-like text format.
+//!
+//! A record is a block of `[Name "value"]` headers followed by the moves,
+//! one numbered turn at a time, and the result:
+//!
+//! ```text
+//! [Light "Livia"]
+//! [Dark "Marcus"]
+//! [Date "2025.03.14"]
+//! [Variant "Schadler"]
+//! [Result "1-0"]
+//!
+//! 1. D4 2. E5 3. C5(D5) ... 17. D4-D3 18. E5xE3 ... 1-0
+//! ```
+//!
+//! Squares use the lettered files and numbered ranks drawn around the board.
+//! A placement is written as its square, a step or slide as `C4-C5`, a jump
+//! as `C4xC6` and a jump chain as `C4xC6xE6`. Pieces removed by the move
+//! follow in parentheses, e.g. `C4-C5(D5,E5)`. The result is `1-0` (Light
+//! wins), `0-1` (Dark wins), `1/2-1/2` (draw) or `*` (unfinished).
+//!
+//! The `Variant` header names the [rule preset](crate::RuleSet::presets) the
+//! game was played under; it defaults to Schadler when missing. A `Board`
+//! header such as `[Board "7x9"]` gives the files and ranks when they differ
+//! from the preset's, and a `MoveLimit` header such as `[MoveLimit "60"]`,
+//! or `[MoveLimit "-"]` for none, gives the
+//! [move limit](crate::RuleSet::move_limit) when it differs. Rules that
+//! match no preset are written as `[Variant "Custom"]` followed by one
+//! header per rule, e.g. `[Movement "rook"]`, `[Pieces "8"]`,
+//! `[JumpsCapture "no"]`, `[MultiJumps "optional"]`, `[Custodial
+//! "all_flanked"]` and `[Dux "yes"]`. Finished games
+//! carry a `Termination` header with the
+//! [reason they ended](crate::GameResult::reason_str), e.g.
+//! `[Termination "elimination"]`. A record that ends in `resignation`,
+//! `timeout` or `agreement` is ended that way on replay. Games that
+//! start from a set-up position rather than an empty board carry a
+//! `[Position "..."]` header in [position notation](crate::notation).
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::game::Game;
+use crate::moves::{Move, MoveError};
+use crate::notation::NotationError;
+use crate::board::Board;
+use crate::position::Position;
+use crate::rules::{CustodialCapture, Movement, MultiJumps, RuleSet};
+use crate::Player;
+
+/// The outcome written at the end of a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordResult {
+    LightWins,
+    DarkWins,
+    Draw,
+    Unfinished,
+}
+
+impl RecordResult {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RecordResult::LightWins => "1-0",
+            RecordResult::DarkWins => "0-1",
+            RecordResult::Draw => "1/2-1/2",
+            RecordResult::Unfinished => "*",
+        }
+    }
+
+    fn parse(token: &str) -> Option<RecordResult> {
+        match token {
+            "1-0" => Some(RecordResult::LightWins),
+            "0-1" => Some(RecordResult::DarkWins),
+            "1/2-1/2" => Some(RecordResult::Draw),
+            "*" => Some(RecordResult::Unfinished),
+            _ => None,
+        }
+    }
+
+    fn of(game: &Game) -> RecordResult {
+        match (game.game_over(), game.winner()) {
+            (false, _) => RecordResult::Unfinished,
+            (true, Some(Player::Light)) => RecordResult::LightWins,
+            (true, Some(Player::Dark)) => RecordResult::DarkWins,
+            (true, None) => RecordResult::Draw,
+        }
+    }
+}
+
+/// A move as written in a record, with the captures the record claims for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedMove {
+    pub mv: Move,
+    pub captured: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    /// A header line is not of the form `[Name "value"]`.
+    InvalidHeader(String),
+    /// A movetext token is not a move, move number or result.
+    InvalidMove(String),
+    /// The `Position` header is not valid position notation.
+    InvalidPosition(NotationError),
+    /// The `Variant` header does not name a rule preset.
+    UnknownVariant(String),
+    /// The `Board` header is not a supported `WIDTHxHEIGHT` size.
+    InvalidBoard(String),
+    /// The `MoveLimit` header is not a positive number of moves or `-`.
+    InvalidMoveLimit(String),
+    /// A rule header such as `Movement` or `Pieces` has a value it does not
+    /// take, or the rules it gives are not playable.
+    InvalidRule { header: String, value: String },
+    /// The record has no result token at the end of the moves.
+    MissingResult,
+    /// Replaying the record failed at the given (1-based) move.
+    IllegalMove { ply: usize, mv: String, reason: MoveError },
+    /// The move was legal but captured different pieces than recorded.
+    CaptureMismatch { ply: usize, mv: String, actual: String },
+    /// The result in the record does not match the replayed game.
+    ResultMismatch { recorded: RecordResult, actual: RecordResult },
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::InvalidHeader(line) => write!(f, "invalid header line: {}", line),
+            RecordError::InvalidMove(token) => write!(f, "invalid move '{}'", token),
+            RecordError::InvalidPosition(e) => write!(f, "invalid Position header: {}", e),
+            RecordError::UnknownVariant(name) => write!(f, "unknown variant '{}'", name),
+            RecordError::InvalidBoard(size) => write!(f, "invalid board size '{}'", size),
+            RecordError::InvalidMoveLimit(limit) => write!(f, "invalid move limit '{}'", limit),
+            RecordError::InvalidRule { header, value } => write!(f, "invalid {} header '{}'", header, value),
+            RecordError::MissingResult => write!(f, "record has no result"),
+            RecordError::IllegalMove { ply, mv, reason } => {
+                write!(f, "move {} ({}) cannot be played: {}", ply, mv, reason)
+            }
+            RecordError::CaptureMismatch { ply, mv, actual } => {
+                write!(f, "move {} ({}) actually captures ({})", ply, mv, actual)
+            }
+            RecordError::ResultMismatch { recorded, actual } => write!(
+                f,
+                "record gives result {} but the game ends {}",
+                recorded.as_str(),
+                actual.as_str()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+/// A whole game: headers, starting position, moves and result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    /// Headers in the order they are written.
+    pub headers: Vec<(String, String)>,
+    /// Starting position and rules, written as a `Position` header unless it
+    /// is the empty board.
+    pub start: Position,
+    pub moves: Vec<RecordedMove>,
+    pub result: RecordResult,
+}
+
+impl GameRecord {
+    /// Records `game` from its starting position, with placeholder player
+    /// and date headers to be filled in with [`GameRecord::set_header`].
+    pub fn from_game(game: &Game) -> GameRecord {
+        let result = RecordResult::of(game);
+        let rules = game.rules();
+        let variant = rules.name().unwrap_or(CUSTOM_VARIANT);
+
+        let mut headers = vec![
+            ("Light".to_string(), "?".to_string()),
+            ("Dark".to_string(), "?".to_string()),
+            ("Date".to_string(), "????.??.??".to_string()),
+            ("Variant".to_string(), variant.to_string()),
+        ];
+        if rules.name().is_none() {
+            headers.extend(rule_headers(rules).map(|(name, value)| (name.to_string(), value)));
+        }
+        let preset = RuleSet::preset(variant);
+        if preset.map(|preset| (preset.width, preset.height)) != Some((rules.width, rules.height)) {
+            headers.push(("Board".to_string(), format!("{}x{}", rules.width, rules.height)));
+        }
+        if preset.map(|preset| preset.move_limit) != Some(rules.move_limit) {
+            let limit = rules.move_limit.map_or("-".to_string(), |limit| limit.to_string());
+            headers.push(("MoveLimit".to_string(), limit));
+        }
+        headers.push(("Result".to_string(), result.as_str().to_string()));
+        if let Some(game_result) = game.result() {
+            headers.push(("Termination".to_string(), game_result.reason_str().to_string()));
+        }
+
+        GameRecord {
+            headers,
+            start: game.initial_position().clone(),
+            moves: game
+                .history()
+                .iter()
+                .map(|played| RecordedMove {
+                    mv: played.mv.clone(),
+                    captured: played.captured.clone(),
+                })
+                .collect(),
+            result,
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Sets a header, replacing an existing one of the same name.
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        match self.headers.iter_mut().find(|(n, _)| n == name) {
+            Some(header) => header.1 = value.to_string(),
+            None => self.headers.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    /// Parses a record without checking that its moves are legal; see
+    /// [`GameRecord::replay`].
+    pub fn parse(text: &str) -> Result<GameRecord, RecordError> {
+        let mut headers = Vec::new();
+        let mut setup = None;
+        let mut movetext = String::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                let (name, value) = parse_header(line)?;
+                if name == "Position" {
+                    setup = Some(value);
+                } else {
+                    headers.push((name, value));
+                }
+            } else {
+                movetext.push_str(line);
+                movetext.push(' ');
+            }
+        }
+
+        let mut rules = match headers.iter().find(|(name, _)| name == "Variant") {
+            // Custom rules start from the default and are given in full by
+            // the rule headers
+            Some((_, variant)) if variant.eq_ignore_ascii_case(CUSTOM_VARIANT) => RuleSet::default(),
+            Some((_, variant)) => {
+                RuleSet::preset(variant).ok_or_else(|| RecordError::UnknownVariant(variant.clone()))?
+            }
+            None => RuleSet::default(),
+        };
+        // Rule headers come first so the board size is checked against the
+        // number of pieces they give
+        for (name, value) in headers.iter().filter(|(name, _)| RULE_HEADERS.contains(&name.as_str())) {
+            rules = with_rule(rules, name, value.trim()).ok_or_else(|| RecordError::InvalidRule {
+                header: name.clone(),
+                value: value.clone(),
+            })?;
+        }
+        if let Some((_, size)) = headers.iter().find(|(name, _)| name == "Board") {
+            let invalid = || RecordError::InvalidBoard(size.clone());
+            let (width, height) = size.split_once(['x', 'X']).ok_or_else(invalid)?;
+            let width = width.trim().parse().map_err(|_| invalid())?;
+            let height = height.trim().parse().map_err(|_| invalid())?;
+            rules = rules.with_board_size(width, height);
+            rules.validate().map_err(|_| invalid())?;
+        }
+        if let Some((_, limit)) = headers.iter().find(|(name, _)| name == "MoveLimit") {
+            let invalid = || RecordError::InvalidMoveLimit(limit.clone());
+            let move_limit = match limit.trim() {
+                "-" => None,
+                limit => Some(limit.parse().map_err(|_| invalid())?),
+            };
+            rules = rules.with_move_limit(move_limit);
+            rules.validate().map_err(|_| invalid())?;
+        }
+        // Only the number of pieces can still make the rules unplayable
+        rules.validate().map_err(|_| RecordError::InvalidRule {
+            header: "Pieces".to_string(),
+            value: rules.pieces_per_side.to_string(),
+        })?;
+        let start = match setup {
+            Some(notation) => Position::from_notation_with_rules(&notation, rules)
+                .map_err(RecordError::InvalidPosition)?,
+            None => Position::with_rules(rules),
+        };
+
+        let board = start.board();
+        let mut moves = Vec::new();
+        let mut result = None;
+        for token in movetext.split_whitespace() {
+            if result.is_some() {
+                return Err(RecordError::InvalidMove(token.to_string()));
+            }
+            if let Some(r) = RecordResult::parse(token) {
+                result = Some(r);
+            } else if token.ends_with('.') && token[..token.len() - 1].parse::<usize>().is_ok() {
+                // Move numbers are only for readers
+                continue;
+            } else {
+                moves.push(parse_move(board, token).ok_or_else(|| RecordError::InvalidMove(token.to_string()))?);
+            }
+        }
+
+        Ok(GameRecord {
+            headers,
+            start: start.clone(),
+            moves,
+            result: result.ok_or(RecordError::MissingResult)?,
+        })
+    }
+
+    /// Plays the record out from its starting position, checking every move,
+    /// its captures and the final result. Stops at the first move that does
+    /// not match the rules.
+    pub fn replay(&self) -> Result<Game, RecordError> {
+        let mut game = Game::from_position(self.start.clone());
+        let board = self.start.board();
+
+        for (i, recorded) in self.moves.iter().enumerate() {
+            let ply = i + 1;
+            let mut actual = game.apply(recorded.mv.clone())
+                .map_err(|reason| RecordError::IllegalMove {
+                    ply,
+                    mv: move_text(board, &recorded.mv, &recorded.captured),
+                    reason,
+                })?;
+            let mut claimed = recorded.captured.clone();
+            actual.sort_unstable();
+            claimed.sort_unstable();
+            if actual != claimed {
+                return Err(RecordError::CaptureMismatch {
+                    ply,
+                    mv: move_text(board, &recorded.mv, &recorded.captured),
+                    actual: square_list(board, &actual),
+                });
+            }
+        }
+
+        // Resignations, timeouts and agreed draws end games the moves alone
+        // do not
+        if !game.game_over() {
+            match (self.header("Termination"), self.result) {
+                (Some("resignation"), RecordResult::LightWins) => game.resign(Player::Dark).ok(),
+                (Some("resignation"), RecordResult::DarkWins) => game.resign(Player::Light).ok(),
+                (Some("timeout"), RecordResult::LightWins) => game.time_out(Player::Dark).ok(),
+                (Some("timeout"), RecordResult::DarkWins) => game.time_out(Player::Light).ok(),
+                (Some("agreement"), RecordResult::Draw) => {
+                    game.offer_draw(game.current_player()).and_then(|_| game.accept_draw()).ok()
+                }
+                _ => None,
+            };
+        }
+
+        let actual = RecordResult::of(&game);
+        if self.result != RecordResult::Unfinished && self.result != actual {
+            return Err(RecordError::ResultMismatch {
+                recorded: self.result,
+                actual,
+            });
+        }
+
+        Ok(game)
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.headers {
+            writeln!(f, "[{} \"{}\"]", name, value)?;
+        }
+        if self.start != Position::with_rules(*self.start.rules()) {
+            writeln!(f, "[Position \"{}\"]", self.start.to_notation())?;
+        }
+        writeln!(f)?;
+
+        let mut tokens: Vec<String> = Vec::new();
+        for (i, recorded) in self.moves.iter().enumerate() {
+            let text = move_text(self.start.board(), &recorded.mv, &recorded.captured);
+            tokens.push(format!("{}. {}", i + 1, text));
+        }
+        tokens.push(self.result.as_str().to_string());
+
+        // Wrap movetext at roughly 80 columns
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + token.len() >= 80 {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = RecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GameRecord::parse(s)
+    }
+}
+
+/// The `Variant` header of rules that match no preset.
+const CUSTOM_VARIANT: &str = "Custom";
+
+/// Headers giving each rule of a custom variant, in the order written.
+const RULE_HEADERS: [&str; 6] = ["Movement", "Pieces", "JumpsCapture", "MultiJumps", "Custodial", "Dux"];
+
+/// The value of each of [`RULE_HEADERS`] for `rules`.
+fn rule_headers(rules: &RuleSet) -> impl Iterator<Item = (&'static str, String)> {
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+    let values = [
+        match rules.movement {
+            Movement::Step => "step",
+            Movement::Rook => "rook",
+        }
+        .to_string(),
+        rules.pieces_per_side.to_string(),
+        yes_no(rules.jumps_capture).to_string(),
+        match rules.multi_jumps {
+            MultiJumps::Never => "never",
+            MultiJumps::Optional => "optional",
+            MultiJumps::Mandatory => "mandatory",
+        }
+        .to_string(),
+        match rules.custodial {
+            CustodialCapture::Disabled => "disabled",
+            CustodialCapture::ByMover => "by_mover",
+            CustodialCapture::AllFlanked => "all_flanked",
+        }
+        .to_string(),
+        yes_no(rules.dux).to_string(),
+    ];
+    RULE_HEADERS.into_iter().zip(values)
+}
+
+/// `rules` with the rule header `name` set to `value`, or `None` if the
+/// header does not take that value.
+fn with_rule(mut rules: RuleSet, name: &str, value: &str) -> Option<RuleSet> {
+    let yes_no = || match value {
+        "yes" => Some(true),
+        "no" => Some(false),
+        _ => None,
+    };
+    match name {
+        "Movement" => {
+            rules.movement = match value {
+                "step" => Movement::Step,
+                "rook" => Movement::Rook,
+                _ => return None,
+            }
+        }
+        "Pieces" => rules.pieces_per_side = value.parse().ok()?,
+        "JumpsCapture" => rules.jumps_capture = yes_no()?,
+        "MultiJumps" => {
+            rules.multi_jumps = match value {
+                "never" => MultiJumps::Never,
+                "optional" => MultiJumps::Optional,
+                "mandatory" => MultiJumps::Mandatory,
+                _ => return None,
+            }
+        }
+        "Custodial" => {
+            rules.custodial = match value {
+                "disabled" => CustodialCapture::Disabled,
+                "by_mover" => CustodialCapture::ByMover,
+                "all_flanked" => CustodialCapture::AllFlanked,
+                _ => return None,
+            }
+        }
+        "Dux" => rules.dux = yes_no()?,
+        _ => return None,
+    }
+    Some(rules)
+}
+
+fn square_list(board: &Board, squares: &[usize]) -> String {
+    squares.iter().map(|&sq| board.square_name(sq)).collect::<Vec<_>>().join(",")
+}
+
+/// The record text for a move, e.g. `C4-C5(D5,E5)`.
+pub(crate) fn move_text(board: &Board, mv: &Move, captured: &[usize]) -> String {
+    let mut text = match mv {
+        Move::Place(square) => board.square_name(*square),
+        Move::Step(from, to) => format!("{}-{}", board.square_name(*from), board.square_name(*to)),
+        Move::Jump(..) | Move::MultiJump(_) => {
+            mv.path().iter().map(|&sq| board.square_name(sq)).collect::<Vec<_>>().join("x")
+        }
+    };
+    if !captured.is_empty() {
+        text.push_str(&format!("({})", square_list(board, captured)));
+    }
+    text
+}
+
+fn parse_header(line: &str) -> Result<(String, String), RecordError> {
+    let invalid = || RecordError::InvalidHeader(line.to_string());
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or_else(invalid)?;
+    let (name, value) = inner.split_once(' ').ok_or_else(invalid)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(invalid)?;
+    if name.is_empty() {
+        return Err(invalid());
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+fn parse_move(board: &Board, token: &str) -> Option<RecordedMove> {
+    let (body, captured) = match token.split_once('(') {
+        Some((body, rest)) => {
+            let list = rest.strip_suffix(')')?;
+            let captured = list
+                .split(',')
+                .map(|name| board.parse_square(name))
+                .collect::<Option<Vec<_>>>()?;
+            (body, captured)
+        }
+        None => (token, Vec::new()),
+    };
+
+    let mv = if let Some((from, to)) = body.split_once('-') {
+        let (from, to) = (board.parse_square(from)?, board.parse_square(to)?);
+        if board.orthogonal_distance(from, to).unwrap_or(0) == 0 {
+            return None;
+        }
+        Move::Step(from, to)
+    } else if body.contains('x') || body.contains('X') {
+        let path = body
+            .split(['x', 'X'])
+            .map(|name| board.parse_square(name))
+            .collect::<Option<Vec<_>>>()?;
+        if !path.windows(2).all(|hop| board.orthogonal_distance(hop[0], hop[1]) == Some(2)) {
+            return None;
+        }
+        Move::from_jump_path(path)
+    } else {
+        Move::Place(board.parse_square(body)?)
+    };
+
+    Some(RecordedMove { mv, captured })
+}
@@ -0,0 +1,177 @@
+//! Rule variants. The rules of Latrones were never written down in full, so
+//! the engine plays whichever reconstruction a [`RuleSet`] describes.
+
+use crate::board::MAX_BOARD_SIZE;
+
+/// Moves without a capture after which the presets draw the game: fifty
+/// for each side.
+pub const DEFAULT_MOVE_LIMIT: u16 = 100;
+
+/// How pieces move in the movement phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Movement {
+    /// One square orthogonally.
+    Step,
+    /// Any number of empty squares orthogonally, like a chess rook. Jumps
+    /// are still over an adjacent piece only.
+    Rook,
+}
+
+/// Whether a piece that has jumped may jump again in the same turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MultiJumps {
+    /// Every jump ends the turn.
+    Never,
+    /// The piece may stop after any jump of a chain.
+    Optional,
+    /// The piece must keep jumping while it can.
+    Mandatory,
+}
+
+/// Which pieces are removed by custodianship (enclosure between two enemies
+/// on opposite sides).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CustodialCapture {
+    /// Pieces are only captured by jumps.
+    Disabled,
+    /// Only the side that moved captures: an enemy piece next to the piece
+    /// that arrived is removed if another of the mover's pieces stands
+    /// directly beyond it. Moving between two enemies is safe.
+    ByMover,
+    /// After every placement or move, every flanked piece on the board is
+    /// removed, whichever side it belongs to.
+    AllFlanked,
+}
+
+/// The rules a game is played under, fixed when the game is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RuleSet {
+    /// Number of files, at most [`MAX_BOARD_SIZE`].
+    pub width: usize,
+    /// Number of ranks, at most [`MAX_BOARD_SIZE`].
+    pub height: usize,
+    pub movement: Movement,
+    /// Pieces each player places during the placement phase. Both sides'
+    /// pieces must fit on the board.
+    pub pieces_per_side: u8,
+    /// Whether a jumped enemy piece is removed. A jump that captures is not
+    /// followed by a custodial capture check.
+    pub jumps_capture: bool,
+    pub multi_jumps: MultiJumps,
+    pub custodial: CustodialCapture,
+    /// Whether each side's first piece is a [dux](crate::PieceKind::Dux). A
+    /// player whose dux is captured, or cannot move on their turn, loses.
+    pub dux: bool,
+    /// Moves in a row, counting both sides, that the movement phase may go
+    /// without a capture before the game is drawn. `None` for no limit.
+    pub move_limit: Option<u16>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::schadler()
+    }
+}
+
+impl RuleSet {
+    /// Schädler's reconstruction: eight pieces each, one-step orthogonal
+    /// moves, a capturing jump ends the turn, custodial captures by the
+    /// side that moved.
+    pub fn schadler() -> RuleSet {
+        RuleSet {
+            width: 8,
+            height: 8,
+            movement: Movement::Step,
+            pieces_per_side: 8,
+            jumps_capture: true,
+            multi_jumps: MultiJumps::Never,
+            custodial: CustodialCapture::ByMover,
+            dux: false,
+            move_limit: Some(DEFAULT_MOVE_LIMIT),
+        }
+    }
+
+    /// The Museum Quintana reconstruction: as Schädler's, but pieces slide
+    /// any distance like a rook.
+    pub fn museum_quintana() -> RuleSet {
+        RuleSet {
+            movement: Movement::Rook,
+            ..RuleSet::schadler()
+        }
+    }
+
+    /// Schädler's reconstruction as first implemented here, where every
+    /// flanked piece is captured after each move, including the mover's own.
+    pub fn schadler_all_flanked() -> RuleSet {
+        RuleSet {
+            custodial: CustodialCapture::AllFlanked,
+            ..RuleSet::schadler()
+        }
+    }
+
+    /// Schädler's reconstruction with a dux for each side.
+    pub fn schadler_dux() -> RuleSet {
+        RuleSet {
+            dux: true,
+            ..RuleSet::schadler()
+        }
+    }
+
+    /// The same rules on a board of a different size, such as the 7×9, 5×5,
+    /// 6×7 and 11×16 boards found at Roman sites.
+    pub fn with_board_size(self, width: usize, height: usize) -> RuleSet {
+        RuleSet { width, height, ..self }
+    }
+
+    /// The same rules with a different limit on moves without a capture.
+    pub fn with_move_limit(self, move_limit: Option<u16>) -> RuleSet {
+        RuleSet { move_limit, ..self }
+    }
+
+    /// Checks that the board size is supported and that both sides' pieces
+    /// fit on it.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if !(2..=MAX_BOARD_SIZE).contains(&self.width) || !(2..=MAX_BOARD_SIZE).contains(&self.height) {
+            return Err("board sides must be between 2 and 16 squares");
+        }
+        if self.pieces_per_side == 0 {
+            return Err("each side needs at least one piece");
+        }
+        if 2 * self.pieces_per_side as usize > self.width * self.height {
+            return Err("both sides' pieces must fit on the board");
+        }
+        if self.move_limit == Some(0) {
+            return Err("the move limit must be at least one move");
+        }
+        Ok(())
+    }
+
+    /// Every preset with the name used for it in game records.
+    pub fn presets() -> Vec<(&'static str, RuleSet)> {
+        vec![
+            ("Schadler", RuleSet::schadler()),
+            ("Museum Quintana", RuleSet::museum_quintana()),
+            ("Schadler Dux", RuleSet::schadler_dux()),
+            ("Schadler All Flanked", RuleSet::schadler_all_flanked()),
+        ]
+    }
+
+    /// Looks up a preset by name, ignoring case.
+    pub fn preset(name: &str) -> Option<RuleSet> {
+        RuleSet::presets()
+            .into_iter()
+            .find(|(preset, _)| preset.eq_ignore_ascii_case(name))
+            .map(|(_, rules)| rules)
+    }
+
+    /// The name of the preset these rules match, if any, ignoring the board
+    /// size and the move limit.
+    pub fn name(&self) -> Option<&'static str> {
+        RuleSet::presets()
+            .into_iter()
+            .find(|(_, rules)| {
+                rules.with_board_size(self.width, self.height).with_move_limit(self.move_limit) == *self
+            })
+            .map(|(name, _)| name)
+    }
+}
@@ -0,0 +1,346 @@
+//! Computer opponent: iterative-deepening alpha-beta search.
+//!
+//! The search works on [`Position`]s, so both phases are covered and a jump
+//! chain is searched as the single move it is. Positions where the game is
+//! over are scored with the same rules [`Game`](crate::Game) uses.
+
+use serde::{Deserialize, Serialize};
+
+use crate::eval::{evaluate_position, EvalWeights};
+use crate::game::winner;
+use crate::moves::Move;
+use crate::position::Position;
+use crate::tt::{Bound, TranspositionTable, TtEntry};
+
+/// Deepest search a time limit will attempt.
+pub const MAX_DEPTH: u8 = 64;
+
+/// Score of a won position, less one for each ply it takes to get there.
+const WIN: i32 = 1_000_000;
+const INFINITY: i32 = WIN + 1;
+/// Scores within this many points of a win or loss are wins or losses.
+const MATE_RANGE: i32 = 1000;
+
+/// Nodes searched between checks of the clock.
+const NODES_PER_CLOCK_CHECK: u64 = 1024;
+
+/// How long to search. Deserializes from a number of plies, e.g. `4`, from
+/// `{ "time_ms": 500 }` or from `{ "depth": 4, "time_ms": 500 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SearchLimit {
+    /// Search this many plies deep. A depth of 0 is treated as 1.
+    Depth(u8),
+    /// Deepen up to `depth` plies, stopping early if the time is used up.
+    /// The first ply is always completed.
+    DepthAndTime { depth: u8, time_ms: u32 },
+    /// Deepen until the time is used up. The first ply is always completed.
+    Time { time_ms: u32 },
+}
+
+impl SearchLimit {
+    /// The deepest iteration and the time after which to stop.
+    fn bounds(self) -> (u8, Option<f64>) {
+        match self {
+            SearchLimit::Depth(depth) => (depth.max(1), None),
+            SearchLimit::DepthAndTime { depth, time_ms } => (depth.max(1), Some(now_ms() + f64::from(time_ms))),
+            SearchLimit::Time { time_ms } => (MAX_DEPTH, Some(now_ms() + f64::from(time_ms))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchResult {
+    /// The move to play, or `None` if the game is already over.
+    pub best_move: Option<Move>,
+    /// Score for the side to move: positive is good for them. Wins and
+    /// losses are scored beyond ±900000.
+    pub score: i32,
+    /// Deepest iteration that was completed.
+    pub depth: u8,
+    pub nodes: u64,
+}
+
+/// Searches positions for the best move. A `Searcher` can be reused from one
+/// move to the next.
+#[derive(Debug, Clone, Default)]
+pub struct Searcher {
+    weights: EvalWeights,
+    table: TranspositionTable,
+    nodes: u64,
+    // Milliseconds since the epoch after which the search stops
+    deadline: Option<f64>,
+}
+
+impl Searcher {
+    pub fn new() -> Searcher {
+        Searcher::default()
+    }
+
+    /// A searcher that scores positions with `weights` instead of the
+    /// defaults.
+    pub fn with_weights(weights: EvalWeights) -> Searcher {
+        Searcher {
+            weights,
+            ..Searcher::default()
+        }
+    }
+
+    /// The same searcher with a transposition table of `entries` results,
+    /// rounded down to a power of two.
+    pub fn with_table_size(self, entries: usize) -> Searcher {
+        Searcher {
+            table: TranspositionTable::new(entries),
+            ..self
+        }
+    }
+
+    /// Forgets the results of earlier searches. Call this before searching
+    /// positions played under different rules.
+    pub fn clear_table(&mut self) {
+        self.table.clear();
+    }
+
+    /// Finds the best move for the side to move in `position`.
+    pub fn search(&mut self, position: &Position, limit: SearchLimit) -> SearchResult {
+        self.nodes = 0;
+        self.deadline = None;
+
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+        };
+        if winner(position).is_some() {
+            return result;
+        }
+
+        let (max_depth, deadline) = limit.bounds();
+
+        let mut moves = position.legal_moves();
+        order_moves(position, &mut moves);
+
+        for depth in 1..=max_depth {
+            // The first iteration always runs to completion so that there is
+            // a move to return
+            if depth > 1 {
+                self.deadline = deadline;
+            }
+            let Some((best, score)) = self.search_root(position, &moves, depth) else {
+                break;
+            };
+
+            // Search the best move first in the next iteration
+            let index = moves.iter().position(|mv| *mv == best).unwrap_or(0);
+            moves[..=index].rotate_right(1);
+
+            result.best_move = Some(best);
+            result.score = score;
+            result.depth = depth;
+            if score.abs() > WIN - MATE_RANGE {
+                break;
+            }
+        }
+
+        result.nodes = self.nodes;
+        result
+    }
+
+    /// Scores every legal move in `position`, best first, by searching each
+    /// as deep as `limit` allows; the scores come from the deepest iteration
+    /// completed. Slower than [`Searcher::search`], which only needs to
+    /// prove that the other moves are worse.
+    pub fn score_moves(&mut self, position: &Position, limit: SearchLimit) -> Vec<(Move, i32)> {
+        self.nodes = 0;
+        self.deadline = None;
+        if winner(position).is_some() {
+            return Vec::new();
+        }
+
+        let (max_depth, deadline) = limit.bounds();
+        let mut moves = position.legal_moves();
+        order_moves(position, &mut moves);
+        let mut scored = Vec::new();
+        for depth in 1..=max_depth {
+            // As in `search`, the first iteration always completes
+            if depth > 1 {
+                self.deadline = deadline;
+            }
+            let Some(mut iteration) = moves
+                .iter()
+                .map(|mv| Some((mv.clone(), self.search_child(position, mv, depth, -INFINITY, INFINITY, 0)?)))
+                .collect::<Option<Vec<(Move, i32)>>>()
+            else {
+                break;
+            };
+            iteration.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+            // Score the best moves first in the next iteration
+            moves = iteration.iter().map(|(mv, _)| mv.clone()).collect();
+            scored = iteration;
+        }
+        scored
+    }
+
+    /// Searches every root move to `depth`, returning the best move and its
+    /// score, or `None` if time ran out.
+    fn search_root(&mut self, position: &Position, moves: &[Move], depth: u8) -> Option<(Move, i32)> {
+        let mut alpha = -INFINITY;
+        let mut best = None;
+        for mv in moves {
+            let score = self.search_child(position, mv, depth, alpha, INFINITY, 0)?;
+            if best.is_none() || score > alpha {
+                alpha = score;
+                best = Some(mv.clone());
+            }
+        }
+        best.map(|mv| (mv, alpha))
+    }
+
+    /// Plays `mv` and searches the result, scoring it for the side to move
+    /// in `position`.
+    fn search_child(
+        &mut self,
+        position: &Position,
+        mv: &Move,
+        depth: u8,
+        alpha: i32,
+        beta: i32,
+        ply: i32,
+    ) -> Option<i32> {
+        let mut child = position.clone();
+        child.play(mv.clone());
+        // The last player to place also makes the first move, so the side to
+        // move does not always change
+        if child.current_player() == position.current_player() {
+            self.alpha_beta(&child, depth - 1, alpha, beta, ply + 1)
+        } else {
+            self.alpha_beta(&child, depth - 1, -beta, -alpha, ply + 1).map(|score| -score)
+        }
+    }
+
+    fn alpha_beta(&mut self, position: &Position, depth: u8, mut alpha: i32, beta: i32, ply: i32) -> Option<i32> {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(NODES_PER_CLOCK_CHECK) && self.deadline.is_some_and(|deadline| now_ms() >= deadline) {
+            return None;
+        }
+
+        if let Some(winner) = winner(position) {
+            let score = WIN - ply;
+            return Some(if winner == position.current_player() { score } else { -score });
+        }
+        if depth == 0 {
+            return Some(evaluate_position(position, position.current_player(), &self.weights));
+        }
+
+        let hash = position.hash();
+        let mut table_move = None;
+        if let Some(entry) = self.table.probe(hash) {
+            if entry.depth >= depth {
+                let score = score_from_table(entry.score, ply);
+                let usable = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                if usable {
+                    return Some(score);
+                }
+            }
+            table_move = entry.best_move.clone();
+        }
+
+        let mut moves = position.legal_moves();
+        order_moves(position, &mut moves);
+        // The best move found here before is searched first
+        if let Some(index) = table_move.and_then(|best| moves.iter().position(|mv| *mv == best)) {
+            moves[..=index].rotate_right(1);
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for mv in &moves {
+            let score = self.search_child(position, mv, depth, alpha, beta, ply)?;
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv.clone());
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.table.store(TtEntry {
+            hash,
+            depth,
+            score: score_to_table(best_score, ply),
+            bound,
+            best_move,
+        });
+        Some(best_score)
+    }
+}
+
+/// Scores near a win or loss count plies from the root; the table stores
+/// them counting from the position itself, so they stay valid wherever it
+/// is reached.
+fn score_to_table(score: i32, ply: i32) -> i32 {
+    if score > WIN - MATE_RANGE {
+        score + ply
+    } else if score < -(WIN - MATE_RANGE) {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn score_from_table(score: i32, ply: i32) -> i32 {
+    if score > WIN - MATE_RANGE {
+        score - ply
+    } else if score < -(WIN - MATE_RANGE) {
+        score + ply
+    } else {
+        score
+    }
+}
+
+/// Sorts `moves` so that the likeliest best moves come first: longer jump
+/// chains, then moves towards the centre of the board.
+fn order_moves(position: &Position, moves: &mut [Move]) {
+    let board = position.board();
+    let centrality = |square: usize| {
+        let (row, col) = board.coords(square);
+        let row_distance = (2 * row as i32 + 1 - board.height() as i32).abs();
+        let col_distance = (2 * col as i32 + 1 - board.width() as i32).abs();
+        -(row_distance + col_distance)
+    };
+    moves.sort_by_cached_key(|mv| {
+        let priority = 1000 * mv.jumped().len() as i32 + centrality(mv.to());
+        std::cmp::Reverse(priority)
+    });
+}
+
+/// Milliseconds since the Unix epoch.
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Milliseconds since the Unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
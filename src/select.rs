@@ -0,0 +1,119 @@
+//! Outcomes of clicking a square in the browser UI.
+//!
+//! A jump chain is chosen whole by clicking the square it ends on. Where
+//! that does not tell the chains apart, the squares it lands on are
+//! clicked one at a time.
+
+use std::fmt;
+
+use serde::Serialize;
+
+/// What an accepted click did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Selection {
+    /// A piece was placed on the square.
+    Placed,
+    /// A piece was selected, or a different piece reselected.
+    Selected,
+    /// A square the selected piece's jump chain lands on was chosen; the
+    /// chain is played once a click tells it apart from the others.
+    Hopped,
+    /// The selected piece moved to the square.
+    Moved,
+}
+
+/// Why a click was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectError {
+    GameOver,
+    /// The index is not a square of the board.
+    OffBoard,
+    /// A piece cannot be placed on an occupied square.
+    Occupied,
+    /// An empty square was clicked with no piece selected.
+    EmptySquare,
+    /// The piece belongs to the opponent.
+    NotYourPiece,
+    /// The piece has no legal move.
+    PieceCannotMove,
+    /// The piece is already selected.
+    AlreadySelected,
+    /// The selected piece cannot move to the square.
+    NotADestination,
+    /// More than one jump chain of the selected piece ends on the square.
+    AmbiguousMove,
+    /// The clock is running but the click came without a timestamp.
+    TimestampRequired,
+}
+
+impl SelectError {
+    /// A stable identifier for JavaScript, e.g. `"not_your_piece"`.
+    pub fn code(self) -> &'static str {
+        match self {
+            SelectError::GameOver => "game_over",
+            SelectError::OffBoard => "off_board",
+            SelectError::Occupied => "occupied",
+            SelectError::EmptySquare => "empty_square",
+            SelectError::NotYourPiece => "not_your_piece",
+            SelectError::PieceCannotMove => "piece_cannot_move",
+            SelectError::AlreadySelected => "already_selected",
+            SelectError::NotADestination => "not_a_destination",
+            SelectError::AmbiguousMove => "ambiguous_move",
+            SelectError::TimestampRequired => "timestamp_required",
+        }
+    }
+}
+
+impl fmt::Display for SelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectError::GameOver => write!(f, "the game is over"),
+            SelectError::OffBoard => write!(f, "that square is not on the board"),
+            SelectError::Occupied => write!(f, "that square is already occupied"),
+            SelectError::EmptySquare => write!(f, "select one of your pieces first"),
+            SelectError::NotYourPiece => write!(f, "that piece belongs to your opponent"),
+            SelectError::PieceCannotMove => write!(f, "that piece has no legal move"),
+            SelectError::AlreadySelected => write!(f, "that piece is already selected"),
+            SelectError::NotADestination => write!(f, "the selected piece cannot move there"),
+            SelectError::AmbiguousMove => {
+                write!(f, "more than one jump ends there; click the squares to jump to one at a time")
+            }
+            SelectError::TimestampRequired => write!(f, "the clock is running, so moves need a timestamp"),
+        }
+    }
+}
+
+impl std::error::Error for SelectError {}
+
+/// The result of a click as JavaScript sees it: `{ ok: true, action }` or
+/// `{ ok: false, code, message }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct SelectResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action: Option<Selection>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl From<Result<Selection, SelectError>> for SelectResponse {
+    fn from(result: Result<Selection, SelectError>) -> Self {
+        match result {
+            Ok(action) => SelectResponse {
+                ok: true,
+                action: Some(action),
+                code: None,
+                message: None,
+            },
+            Err(error) => SelectResponse {
+                ok: false,
+                action: None,
+                code: Some(error.code()),
+                message: Some(error.to_string()),
+            },
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Pieces in danger: what the opponent could capture if it were their move.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::game::winner;
+use crate::moves::Move;
+use crate::position::{GamePhase, Position};
+
+/// A piece of the side to move that the opponent could capture with their
+/// next move, and every move that would capture it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Threat {
+    pub square: usize,
+    pub moves: Vec<Move>,
+}
+
+impl Position {
+    /// Pieces of the side to move that the opponent could capture, by
+    /// custodianship or by jumping, if it were the opponent's turn. Found
+    /// by playing every opponent move, so every capture rule in play is
+    /// covered. Ordered by square; empty once the game is over.
+    pub fn threats(&self) -> Vec<Threat> {
+        if winner(self).is_some() {
+            return Vec::new();
+        }
+        let mut enemy_turn = self.clone();
+        enemy_turn.switch_player();
+        let enemy = enemy_turn.current_player();
+        if self.phase() == GamePhase::Placement && self.pieces_placed(enemy) == self.rules().pieces_per_side {
+            return Vec::new();
+        }
+
+        let mut threats: BTreeMap<usize, Vec<Move>> = BTreeMap::new();
+        for mv in enemy_turn.legal_moves() {
+            let mut after = enemy_turn.clone();
+            for square in after.play(mv.clone()) {
+                // With every flanked piece removed, the opponent's move can
+                // also cost them their own pieces
+                if self.square(square).owner() == Some(self.current_player()) {
+                    threats.entry(square).or_default().push(mv.clone());
+                }
+            }
+        }
+        threats
+            .into_iter()
+            .map(|(square, moves)| Threat { square, moves })
+            .collect()
+    }
+}
@@ -0,0 +1,79 @@
+//! Fixed-size transposition table for the alpha-beta search, indexed by
+//! [`Position::hash`](crate::Position::hash).
+
+use crate::moves::Move;
+
+/// What a stored score says about the true score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The true score is at least this (the search failed high).
+    Lower,
+    /// The true score is at most this (the search failed low).
+    Upper,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtEntry {
+    pub hash: u64,
+    pub depth: u8,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+}
+
+/// A table of search results that never grows: each hash maps to one slot
+/// and a new result replaces the old one unless the old one is for the same
+/// position and searched deeper.
+#[derive(Debug, Clone)]
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        TranspositionTable::new(TranspositionTable::DEFAULT_ENTRIES)
+    }
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_ENTRIES: usize = 1 << 16;
+
+    /// A table with room for `entries` results, rounded down to a power of
+    /// two (and at least one).
+    pub fn new(entries: usize) -> TranspositionTable {
+        let size = if entries <= 1 { 1 } else { 1 << entries.ilog2() };
+        TranspositionTable {
+            entries: vec![None; size],
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The stored result for the position with `hash`, if there is one.
+    pub fn probe(&self, hash: u64) -> Option<&TtEntry> {
+        self.entries[self.slot(hash)]
+            .as_ref()
+            .filter(|entry| entry.hash == hash)
+    }
+
+    pub fn store(&mut self, entry: TtEntry) {
+        let slot = self.slot(entry.hash);
+        let keep_old = self.entries[slot]
+            .as_ref()
+            .is_some_and(|old| old.hash == entry.hash && old.depth > entry.depth);
+        if !keep_old {
+            self.entries[slot] = Some(entry);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    fn slot(&self, hash: u64) -> usize {
+        (hash as usize) & (self.entries.len() - 1)
+    }
+}
@@ -0,0 +1,89 @@
+//! Zobrist keys for hashing positions.
+//!
+//! A position's hash is the XOR of one key per occupied square, a key if
+//! Dark is to move, a key in the movement phase, and a key for each
+//! player's placed-piece counter, so a move updates it by XORing out what
+//! changed. Jump chains are whole moves, so there is no pending-jump state
+//! to hash.
+
+use crate::board::{PieceKind, Square, MAX_BOARD_SIZE};
+use crate::position::{GamePhase, Position};
+use crate::Player;
+
+const SQUARES: usize = MAX_BOARD_SIZE * MAX_BOARD_SIZE;
+// Four piece keys per square, then Dark to move, movement phase, and 256
+// counter values for each player
+const SIDE: usize = 4 * SQUARES;
+const PHASE: usize = SIDE + 1;
+const COUNTERS: usize = PHASE + 1;
+const KEY_COUNT: usize = COUNTERS + 2 * 256;
+
+static KEYS: [u64; KEY_COUNT] = generate_keys();
+
+/// SplitMix64 over a fixed seed, so hashes are the same in every build.
+const fn generate_keys() -> [u64; KEY_COUNT] {
+    let mut keys = [0; KEY_COUNT];
+    let mut state: u64 = 0x4C61_7472_6F6E_6573;
+    let mut i = 0;
+    while i < KEY_COUNT {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+/// Key of `square` holding `contents`; zero for an empty square.
+pub(crate) fn square_key(square: usize, contents: Square) -> u64 {
+    let piece = match contents {
+        Square::Empty => return 0,
+        Square::Piece(Player::Light, PieceKind::Soldier) => 0,
+        Square::Piece(Player::Dark, PieceKind::Soldier) => 1,
+        Square::Piece(Player::Light, PieceKind::Dux) => 2,
+        Square::Piece(Player::Dark, PieceKind::Dux) => 3,
+    };
+    KEYS[4 * square + piece]
+}
+
+/// Key of the side to move; zero for Light.
+pub(crate) fn side_key(player: Player) -> u64 {
+    match player {
+        Player::Light => 0,
+        Player::Dark => KEYS[SIDE],
+    }
+}
+
+/// Key of the phase; zero for placement.
+pub(crate) fn phase_key(phase: GamePhase) -> u64 {
+    match phase {
+        GamePhase::Placement => 0,
+        GamePhase::Movement => KEYS[PHASE],
+    }
+}
+
+/// Key of `player` having placed `count` pieces.
+pub(crate) fn placed_key(player: Player, count: u8) -> u64 {
+    let offset = match player {
+        Player::Light => 0,
+        Player::Dark => 256,
+    };
+    KEYS[COUNTERS + offset + usize::from(count)]
+}
+
+/// The hash of `position` computed from scratch.
+pub(crate) fn hash(position: &Position) -> u64 {
+    let squares = position
+        .board()
+        .squares()
+        .iter()
+        .enumerate()
+        .fold(0, |hash, (i, &square)| hash ^ square_key(i, square));
+    squares
+        ^ side_key(position.current_player())
+        ^ phase_key(position.phase())
+        ^ placed_key(Player::Light, position.pieces_placed(Player::Light))
+        ^ placed_key(Player::Dark, position.pieces_placed(Player::Dark))
+}
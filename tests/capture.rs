@@ -0,0 +1,48 @@
+//! Custodial captures under the two rules that remove flanked pieces.
+
+use latrones::{CustodialCapture, Game, GameState, Move, Player, Position, RuleSet, Square};
+
+/// Light's piece on B1 can step up between the Dark pieces on A2 and C2.
+const BETWEEN: &str = "7L/8/8/8/8/8/D1D5/1L6 l m 8 8";
+/// Light's piece on B1 can step up next to the Dark piece on C2, which has
+/// Light's piece on D2 beyond it.
+const FLANKING: &str = "7L/8/8/8/8/8/2DL4/1L5D l m 8 8";
+
+fn position(notation: &str, custodial: CustodialCapture) -> Position {
+    let rules = RuleSet {
+        custodial,
+        ..RuleSet::schadler()
+    };
+    Position::from_notation_with_rules(notation, rules).unwrap()
+}
+
+#[test]
+fn moving_between_two_enemies_is_safe_by_mover() {
+    let mut position = position(BETWEEN, CustodialCapture::ByMover);
+    assert_eq!(position.apply(Move::Step(1, 9)), Ok(Vec::new()));
+    assert_eq!(position.square(9).owner(), Some(Player::Light));
+}
+
+#[test]
+fn moving_between_two_enemies_is_captured_when_all_flanked() {
+    let mut position = position(BETWEEN, CustodialCapture::AllFlanked);
+    assert_eq!(position.apply(Move::Step(1, 9)), Ok(vec![9]));
+    assert_eq!(position.square(9), Square::Empty);
+}
+
+#[test]
+fn mover_captures_the_flanked_enemy() {
+    let flanking = || position(FLANKING, CustodialCapture::ByMover);
+    let mut after = flanking();
+    assert_eq!(after.apply(Move::Step(1, 9)), Ok(vec![10]));
+    assert_eq!(after.square(10), Square::Empty);
+
+    let mut game = Game::from_position(flanking());
+    assert_eq!(game.apply(Move::Step(1, 9)), Ok(vec![10]));
+    assert_eq!(game.history()[0].captured, vec![10]);
+
+    let mut state = GameState::from_game(Game::from_position(flanking()));
+    state.select(1).unwrap();
+    state.select(9).unwrap();
+    assert_eq!(state.last_captured(), vec![10]);
+}
@@ -0,0 +1,37 @@
+//! Timed games played through square clicks.
+
+use latrones::{Clock, Game, GameResult, GameState, Player, SelectError, TimeControl, WinReason};
+
+fn timed_game() -> GameState {
+    let mut state = GameState::from_game(Game::new());
+    state.set_clock(Some(Clock::new(TimeControl::SuddenDeath { base_ms: 1000 })));
+    state
+}
+
+#[test]
+fn timed_moves_cannot_be_taken_back() {
+    let mut state = timed_game();
+    state.select_at(0, Some(0)).unwrap();
+    state.select_at(1, Some(100)).unwrap();
+    assert!(!state.can_undo());
+    assert!(!state.undo());
+    assert_eq!(state.game().history().len(), 2);
+}
+
+#[test]
+fn timeout_survives_undo() {
+    let mut state = timed_game();
+    state.select_at(0, Some(0)).unwrap();
+    state.select_at(1, Some(100)).unwrap();
+    // Light's whole second runs out before their next move
+    assert_eq!(state.select_at(2, Some(1100)), Err(SelectError::GameOver));
+    let timeout = Some(GameResult::Win {
+        winner: Player::Dark,
+        reason: WinReason::Timeout,
+    });
+    assert_eq!(state.game().result(), timeout);
+
+    assert!(!state.undo());
+    assert_eq!(state.game().result(), timeout);
+    assert_eq!(state.game().history().len(), 2);
+}
@@ -0,0 +1,50 @@
+//! Draws by threefold repetition and by the move limit.
+
+use latrones::{DrawReason, Game, GameResult, Move, Position, RuleSet};
+
+/// Plays `moves` as steps, checking that the game goes on after each.
+fn play_steps(game: &mut Game, moves: &[(usize, usize)]) {
+    for &(from, to) in moves {
+        assert_eq!(game.result(), None, "the game ended before {from}-{to}");
+        game.apply(Move::Step(from, to)).unwrap();
+    }
+}
+
+fn draw(reason: DrawReason) -> Option<GameResult> {
+    Some(GameResult::Draw { reason })
+}
+
+#[test]
+fn third_repetition_draws() {
+    let mut game = Game::starting();
+    let shuffle = [(7, 6), (0, 1), (6, 7), (1, 0)];
+    // The starting position comes back after each shuffle
+    play_steps(&mut game, &shuffle);
+    play_steps(&mut game, &shuffle[..3]);
+    assert_eq!(game.result(), None);
+    game.apply(Move::Step(1, 0)).unwrap();
+    assert_eq!(game.result(), draw(DrawReason::Repetition));
+}
+
+#[test]
+fn move_limit_draws() {
+    let mut game = Game::starting_with_rules(RuleSet::schadler().with_move_limit(Some(6)));
+    play_steps(&mut game, &[(7, 6), (0, 1), (15, 14), (8, 9), (23, 22)]);
+    assert_eq!(game.result(), None);
+    game.apply(Move::Step(16, 17)).unwrap();
+    assert_eq!(game.result(), draw(DrawReason::MoveLimit));
+}
+
+#[test]
+fn capture_resets_the_move_limit() {
+    // Light's step from B1 to B2 captures the Dark piece on C2
+    let rules = RuleSet::schadler().with_move_limit(Some(4));
+    let position = Position::from_notation_with_rules("D6L/D7/8/8/8/8/2DL4/1L6 l m 8 8", rules).unwrap();
+    let mut game = Game::from_position(position);
+    play_steps(&mut game, &[(63, 55), (56, 57)]);
+    assert_eq!(game.apply(Move::Step(1, 9)), Ok(vec![10]));
+    play_steps(&mut game, &[(57, 58), (55, 47), (58, 59)]);
+    assert_eq!(game.result(), None);
+    game.apply(Move::Step(47, 39)).unwrap();
+    assert_eq!(game.result(), draw(DrawReason::MoveLimit));
+}
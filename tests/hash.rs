@@ -0,0 +1,50 @@
+//! The hash kept up to date move by move matches the hash of the same
+//! position read back from scratch.
+
+use latrones::{Game, Move, MultiJumps, Position, RuleSet};
+
+fn rule_sets() -> Vec<RuleSet> {
+    let mut rules: Vec<RuleSet> = RuleSet::presets().into_iter().map(|(_, rules)| rules).collect();
+    for multi_jumps in [MultiJumps::Optional, MultiJumps::Mandatory] {
+        rules.push(RuleSet {
+            multi_jumps,
+            ..RuleSet::schadler_dux()
+        });
+    }
+    rules.push(RuleSet::schadler().with_board_size(11, 16));
+    rules
+}
+
+fn from_scratch(position: &Position) -> u64 {
+    Position::from_notation_with_rules(&position.to_notation(), *position.rules())
+        .unwrap()
+        .hash()
+}
+
+#[test]
+fn incremental_hash_matches_full_hash() {
+    let mut chains = 0;
+    let mut captures = 0;
+    for rules in rule_sets() {
+        for seed in 1..=20u64 {
+            let mut game = if seed % 2 == 0 {
+                Game::starting_with_rules(rules)
+            } else {
+                Game::with_rules(rules)
+            };
+            let mut state = seed;
+            while !game.game_over() && game.history().len() < 150 {
+                assert_eq!(game.position().hash(), from_scratch(game.position()));
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let moves = game.legal_moves();
+                let mv = moves[state as usize % moves.len()].clone();
+                chains += usize::from(matches!(mv, Move::MultiJump(_)));
+                captures += game.apply(mv).unwrap().len();
+            }
+            assert_eq!(game.position().hash(), from_scratch(game.position()));
+        }
+    }
+    assert!(chains > 0 && captures > 0, "{chains} chains, {captures} captures");
+}
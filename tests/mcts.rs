@@ -0,0 +1,46 @@
+//! Monte Carlo tree search is reproducible for a given seed.
+
+use latrones::{MctsConfig, MctsResult, MctsSearcher, Position};
+
+fn search(position: &Position, seed: u64, playouts: u32) -> MctsResult {
+    MctsSearcher::new(MctsConfig {
+        playouts,
+        seed,
+        ..MctsConfig::default()
+    })
+    .search(position)
+}
+
+#[test]
+fn same_seed_gives_same_move() {
+    for position in [Position::new(), Position::starting()] {
+        let first = search(&position, 7, 100);
+        assert!(first.best_move.is_some());
+        assert_eq!(search(&position, 7, 100), first);
+
+        // The searcher restarts from its seed on every search
+        let mut searcher = MctsSearcher::new(MctsConfig {
+            playouts: 100,
+            seed: 7,
+            ..MctsConfig::default()
+        });
+        assert_eq!(searcher.search(&position), first);
+        assert_eq!(searcher.search(&position), first);
+    }
+}
+
+#[test]
+fn different_seeds_can_differ() {
+    let position = Position::new();
+    let moves: Vec<_> = (0..10).map(|seed| search(&position, seed, 50).best_move).collect();
+    assert!(moves.iter().any(|mv| *mv != moves[0]), "{moves:?}");
+}
+
+#[test]
+fn zero_playouts_still_returns_a_move() {
+    let position = Position::starting();
+    let result = search(&position, 0, 0);
+    let mv = result.best_move.expect("a move");
+    assert!(position.legal_moves().contains(&mv));
+    assert_eq!(result.visits, 1);
+}
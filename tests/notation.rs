@@ -0,0 +1,56 @@
+//! Position notation that must be rejected, and why.
+
+use latrones::{NotationError, Player, Position, RuleSet};
+
+const START: &str = "D6L/D6L/D6L/D6L/D6L/D6L/D6L/D6L l m 8 8";
+
+fn error(notation: &str) -> NotationError {
+    Position::from_notation(notation).unwrap_err()
+}
+
+#[test]
+fn starting_setup_reads_back() {
+    assert_eq!(Position::from_notation(START).unwrap().to_notation(), START);
+}
+
+#[test]
+fn long_runs_of_digits_are_errors() {
+    assert!(Position::from_notation("99999999999999999999999 l m 8 8").is_err());
+    assert!(Position::from_notation("99999999999999999999999/8/8/8/8/8/8/8 l p 0 0").is_err());
+}
+
+#[test]
+fn malformed_fields() {
+    assert_eq!(error("8/8/8/8/8/8/8/8 l m"), NotationError::FieldCount(3));
+    assert_eq!(
+        Position::from_notation_with_rules("8/8/8 l p 0 0", RuleSet::default()).unwrap_err(),
+        NotationError::RankCount { expected: 8, found: 3 }
+    );
+    assert_eq!(
+        error("8/8/8/8/8/8/8/7 l p 0 0"),
+        NotationError::RankLength {
+            rank: 1,
+            expected: 8,
+            found: 7
+        }
+    );
+    assert_eq!(error("8/8/8/8/8/8/8/7x l p 0 0"), NotationError::InvalidSquare('x'));
+    assert_eq!(error("8/8/8/8/8/8/8/+7 l p 0 0"), NotationError::InvalidSquare('+'));
+    assert_eq!(error("8/8/8/8/8/8/8/8 x p 0 0"), NotationError::InvalidSideToMove("x".to_string()));
+    assert_eq!(error("8/8/8/8/8/8/8/8 l x 0 0"), NotationError::InvalidPhase("x".to_string()));
+    assert_eq!(
+        error("8/8/8/8/8/8/8/8 l p 9 0"),
+        NotationError::InvalidCounter {
+            counter: "9".to_string(),
+            max: 8
+        }
+    );
+}
+
+#[test]
+fn unreachable_positions() {
+    assert_eq!(error("8/8/8/8/8/8/8/LL6 l p 1 1"), NotationError::TooManyPieces(Player::Light));
+    assert_eq!(error("8/8/8/8/8/8/8/L+D6 d p 1 1"), NotationError::InvalidDux(Player::Light));
+    assert!(matches!(error("8/8/8/8/8/8/8/8 l m 0 0"), NotationError::Inconsistent(_)));
+    assert!(matches!(error("8/8/8/8/8/8/8/L7 l p 1 0"), NotationError::Inconsistent(_)));
+}
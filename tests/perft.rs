@@ -0,0 +1,64 @@
+//! Perft regression numbers from the starting setup (`set_starting_pieces`)
+//! under each preset, and from an empty board. A change in any count means
+//! move generation has changed.
+
+use latrones::{Position, RuleSet};
+
+fn starting(preset: &str) -> Position {
+    Position::starting_with_rules(RuleSet::preset(preset).expect("unknown preset"))
+}
+
+fn assert_perft(position: &Position, expected: &[u64]) {
+    for (depth, &count) in (1..).zip(expected) {
+        assert_eq!(position.perft(depth), count, "perft({depth})");
+    }
+}
+
+#[test]
+fn schadler() {
+    assert_perft(&starting("Schadler"), &[8, 64, 800, 10_000, 136_000]);
+}
+
+#[test]
+fn museum_quintana() {
+    assert_perft(&starting("Museum Quintana"), &[48, 2_144, 113_672]);
+}
+
+#[test]
+fn schadler_dux() {
+    assert_perft(&starting("Schadler Dux"), &[8, 64, 800, 10_000, 136_000]);
+}
+
+#[test]
+fn schadler_all_flanked() {
+    assert_perft(&starting("Schadler All Flanked"), &[8, 64, 800, 10_000, 136_000]);
+}
+
+#[test]
+fn placement() {
+    assert_perft(&Position::new(), &[64, 4_032, 249_984]);
+}
+
+/// Deep enough for the dux rules to change the count. Slow without
+/// optimizations.
+#[test]
+#[ignore]
+fn deep() {
+    assert_eq!(starting("Schadler").perft(7), 28_108_480);
+    assert_eq!(starting("Schadler Dux").perft(7), 28_067_679);
+    assert_eq!(starting("Schadler All Flanked").perft(7), 28_108_480);
+}
+
+#[test]
+fn divide_adds_up_to_perft() {
+    let position = starting("Museum Quintana");
+    let divided = position.divide(3);
+    assert_eq!(divided.len(), 48);
+    assert_eq!(divided.iter().map(|(_, count)| count).sum::<u64>(), position.perft(3));
+}
+
+#[test]
+#[should_panic(expected = "at least 1")]
+fn divide_needs_a_first_move() {
+    starting("Schadler").divide(0);
+}
@@ -0,0 +1,68 @@
+//! Game records written with `GameRecord::from_game` read back as the same
+//! game, under the presets and under rules that match no preset.
+
+use latrones::{CustodialCapture, Game, GameRecord, Movement, MultiJumps, RuleSet};
+
+/// Plays up to `plies` moves chosen by a fixed xorshift sequence.
+fn random_game(rules: RuleSet, seed: u64, plies: usize) -> Game {
+    let mut game = Game::with_rules(rules);
+    let mut state = seed;
+    for _ in 0..plies {
+        let moves = game.legal_moves();
+        if game.game_over() || moves.is_empty() {
+            break;
+        }
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        game.apply(moves[state as usize % moves.len()].clone()).unwrap();
+    }
+    game
+}
+
+fn assert_round_trip(rules: RuleSet) {
+    for seed in 1..=10 {
+        let game = random_game(rules, seed, 120);
+        let text = GameRecord::from_game(&game).to_string();
+        let replayed = text
+            .parse::<GameRecord>()
+            .and_then(|record| record.replay())
+            .unwrap_or_else(|e| panic!("{e}\n{text}"));
+        assert_eq!(replayed.rules(), game.rules(), "{text}");
+        assert_eq!(replayed.history(), game.history(), "{text}");
+        assert_eq!(replayed.result(), game.result(), "{text}");
+    }
+}
+
+#[test]
+fn presets() {
+    for (_, rules) in RuleSet::presets() {
+        assert_round_trip(rules);
+        assert_round_trip(rules.with_board_size(7, 9).with_move_limit(None));
+    }
+}
+
+#[test]
+fn custom_rules() {
+    let schadler = RuleSet::schadler();
+    assert_round_trip(RuleSet {
+        multi_jumps: MultiJumps::Optional,
+        ..schadler
+    });
+    assert_round_trip(RuleSet {
+        jumps_capture: false,
+        multi_jumps: MultiJumps::Mandatory,
+        ..schadler
+    });
+    assert_round_trip(RuleSet {
+        movement: Movement::Rook,
+        custodial: CustodialCapture::Disabled,
+        dux: true,
+        ..schadler
+    });
+    assert_round_trip(RuleSet {
+        pieces_per_side: 3,
+        custodial: CustodialCapture::AllFlanked,
+        ..schadler.with_board_size(3, 3).with_move_limit(Some(20))
+    });
+}
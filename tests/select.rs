@@ -0,0 +1,67 @@
+//! Clicking squares to play jump chains that end on the same square.
+
+use latrones::{Game, GameState, Move, Movement, MultiJumps, Position, RuleSet, SelectError, Selection};
+
+/// Light's piece on A1 can jump to C3 by way of C1 or of A3, and may stop
+/// after any jump.
+fn two_routes() -> GameState {
+    let rules = RuleSet {
+        multi_jumps: MultiJumps::Optional,
+        ..RuleSet::schadler()
+    };
+    let position = Position::from_notation_with_rules("7L/8/8/8/8/1D6/D1D5/LD6 l m 8 8", rules).unwrap();
+    GameState::from_game(Game::from_position(position))
+}
+
+#[test]
+fn shared_end_square_is_ambiguous() {
+    let mut state = two_routes();
+    assert_eq!(state.select(0), Ok(Selection::Selected));
+    assert_eq!(state.select(18), Err(SelectError::AmbiguousMove));
+    assert_eq!(state.selected_path(), vec![0]);
+    assert!(state.game().history().is_empty());
+}
+
+#[test]
+fn chain_is_picked_hop_by_hop() {
+    let mut state = two_routes();
+    state.select(0).unwrap();
+    // C1 could end the move or lead on, and so could C3 after it
+    assert_eq!(state.select(2), Ok(Selection::Hopped));
+    assert_eq!(state.select(18), Ok(Selection::Hopped));
+    assert_eq!(state.selected_path(), vec![0, 2, 18]);
+    assert_eq!(state.select(18), Ok(Selection::Moved));
+    assert_eq!(state.game().history()[0].mv, Move::MultiJump(vec![0, 2, 18]));
+    assert_eq!(state.selected_square(), None);
+}
+
+
+/// Light's rook-moving piece on A1 can slide straight up to A3, or jump
+/// there by way of C1 and C3.
+fn slide_or_chain() -> GameState {
+    let rules = RuleSet {
+        movement: Movement::Rook,
+        multi_jumps: MultiJumps::Optional,
+        ..RuleSet::schadler()
+    };
+    let position = Position::from_notation_with_rules("7L/8/8/8/8/1D6/2D5/LD6 l m 8 8", rules).unwrap();
+    GameState::from_game(Game::from_position(position))
+}
+
+#[test]
+fn direct_move_wins_over_chain_to_same_square() {
+    let mut state = slide_or_chain();
+    state.select(0).unwrap();
+    assert_eq!(state.select(16), Ok(Selection::Moved));
+    assert_eq!(state.game().history()[0].mv, Move::Step(0, 16));
+}
+
+#[test]
+fn chain_to_a_direct_move_square_is_picked_hop_by_hop() {
+    let mut state = slide_or_chain();
+    state.select(0).unwrap();
+    assert_eq!(state.select(2), Ok(Selection::Hopped));
+    assert_eq!(state.select(18), Ok(Selection::Hopped));
+    assert_eq!(state.select(16), Ok(Selection::Moved));
+    assert_eq!(state.game().history()[0].mv, Move::MultiJump(vec![0, 2, 18, 16]));
+}
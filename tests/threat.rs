@@ -0,0 +1,38 @@
+//! Pieces in danger under the rule that removes every flanked piece.
+
+use latrones::{Game, Move, Position, RuleSet};
+
+#[test]
+fn all_flanked_threats_are_the_side_to_moves_pieces() {
+    // Dark's piece on B1 would be removed itself by stepping between
+    // Light's pieces on A2 and C2; Light's piece on E1 can be taken by the
+    // jump from D1 or by flanking it from F1
+    let position =
+        Position::from_notation_with_rules("8/8/8/8/8/8/L1L2D2/1D1DL3 l m 8 8", RuleSet::schadler_all_flanked())
+            .unwrap();
+    let threats = position.threats();
+    assert_eq!(threats.iter().map(|threat| threat.square).collect::<Vec<_>>(), vec![4]);
+    assert!(threats[0].moves.contains(&Move::Jump(3, 4, 5)));
+    assert!(threats[0].moves.contains(&Move::Step(13, 5)));
+}
+
+#[test]
+fn threats_in_random_games_are_the_side_to_moves_pieces() {
+    for (_, rules) in RuleSet::presets() {
+        for seed in 1..=20u64 {
+            let mut game = Game::with_rules(rules);
+            let mut state = seed;
+            while !game.game_over() && game.history().len() < 100 {
+                let position = game.position();
+                for threat in position.threats() {
+                    assert_eq!(position.square(threat.square).owner(), Some(position.current_player()));
+                }
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let moves = game.legal_moves();
+                game.apply(moves[state as usize % moves.len()].clone()).unwrap();
+            }
+        }
+    }
+}
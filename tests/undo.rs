@@ -0,0 +1,38 @@
+//! Taking back moves around results that no move brought about.
+
+use latrones::{Game, Move, Player};
+
+fn game_with_moves(count: usize) -> Game {
+    let mut game = Game::new();
+    for square in 0..count {
+        game.apply(Move::Place(square)).unwrap();
+    }
+    game
+}
+
+#[test]
+fn resignation_and_agreed_draw_cannot_be_undone() {
+    let mut resigned = game_with_moves(2);
+    resigned.resign(Player::Light).unwrap();
+    let mut agreed = game_with_moves(2);
+    agreed.offer_draw(Player::Light).unwrap();
+    agreed.accept_draw().unwrap();
+
+    for mut game in [resigned, agreed] {
+        let result = game.result();
+        assert!(!game.can_undo());
+        assert!(!game.undo());
+        assert_eq!(game.result(), result);
+        assert_eq!(game.history().len(), 2);
+    }
+}
+
+#[test]
+fn redo_fails_once_the_game_is_over() {
+    let mut game = game_with_moves(2);
+    assert!(game.undo());
+    game.resign(Player::Dark).unwrap();
+    assert!(!game.can_redo());
+    assert!(!game.redo());
+    assert_eq!(game.history().len(), 1);
+}